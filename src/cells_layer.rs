@@ -1,7 +1,17 @@
-use std::{usize, isize};
-
 // imports:
-use rand::Rng;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use serde::Deserialize;
+
+use crate::electricity_layer::Electric;
+use crate::emitter_layer::{Consumes, Emission};
+use crate::gravity_layer::{Gravity, GravityLayer};
+use crate::material_registry::MaterialRegistry;
+use crate::particle_layer::{Particle, ParticleLayer};
+use crate::rigid_body_layer::RigidBodyLayer;
+use crate::texture_layer::{shade, Texture};
 
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -12,29 +22,121 @@ pub enum CustomErrors {
     CouldNotComplete
 }
 
+/// The temperature in ```Kelvin``` that every cell slowly relaxes towards
+pub const AMBIENT_TEMP: u16 = 298;
+
+/// The fraction of the difference to ```AMBIENT_TEMP``` that a cell loses per tick
+const AMBIENT_RELAXATION: f32 = 0.001;
+
+/// Scales the combined ```temp_coefficient``` of two neighbours into the fraction of their temperature difference that is exchanged per tick
+const HEAT_EXCHANGE_RATE: f32 = 0.5;
+
+/// Upper bound of the per tick exchange fraction. Anything above 0.25 makes the explicit diffusion overshoot and oscillate
+const MAX_HEAT_EXCHANGE: f32 = 0.2;
+
+/// Fire refreshes its lifetime by this many ticks whenever it manages to ignite a neighbour, so that it lasts as long as it has fuel
+const FIRE_FUEL_BONUS: u16 = 10;
+
+/// The temperature in ```Kelvin``` above which a ```flammable``` cell can ignite without touching fire
+const IGNITION_TEMP: u16 = 573;
+
+/// The most ```explosive``` cells that can join a single detonation
+const MAX_CHARGE: usize = 1024;
+
+/// The blast radius of a single ```explosive``` cell
+const BASE_BLAST_RADIUS: f32 = 3.0;
+
+/// How much the blast radius grows with the square root of the connected charge
+const BLAST_RADIUS_PER_CHARGE: f32 = 1.0;
+
+/// The largest blast radius, no matter how big the charge is
+const MAX_BLAST_RADIUS: f32 = 24.0;
+
+/// The heat in ```Kelvin``` that a blast adds at its center. It falls off linearly towards ```BLAST_HEAT_REACH``` times the blast radius
+const BLAST_HEAT: f32 = 900.0;
+const BLAST_HEAT_REACH: f32 = 1.5;
+
+/// The speed in cells per tick that debris is thrown with from the center of a blast
+const DEBRIS_SPEED: f32 = 6.0;
+
+/// The chance that a cell cleared by a blast catches fire
+const BLAST_FIRE_CHANCE: f64 = 0.3;
+
+/// The ```strength``` of a freshly built ```CellType::ACID``` cell
+const ACID_STRENGTH: u8 = 100;
+
+/// The ```strength``` that acid uses up for every cell it dissolves
+const ACID_COST: u8 = 25;
+
+/// The chance per tick that acid dissolves a neighbour without any ```corrosion_resistance```
+const ACID_REACTIVITY: f32 = 0.2;
+
+/// The chance that a dissolved cell releases ```CellType::TOXIC_GAS``` instead of vanishing
+const ACID_GAS_CHANCE: f64 = 0.3;
+
+/// The velocity in cells per tick that a falling cell gains every tick
+pub const GRAVITY: f32 = 0.4;
+
+/// The highest velocity in cells per tick that a cell can reach, must not be larger than ```UPDATE_REACH```
+pub const MAX_VELOCITY: f32 = 8.0;
+
+/// The fraction of its vertical velocity that a cell turns into sideways velocity when it lands
+const LANDING_SPREAD: f32 = 0.4;
+
+/// The fraction of its sideways velocity that a sliding cell keeps per tick
+const FRICTION: f32 = 0.7;
+
+/// The speed in cells per tick from which a landing liquid cell may splash
+const SPLASH_SPEED: f32 = 4.0;
+
+/// The chance that a liquid cell landing faster than ```SPLASH_SPEED``` splashes up as a ```Particle```
+const SPLASH_CHANCE: f64 = 0.2;
+
+/// The chance per tick that a gas tries to rise before it drifts in a random direction
+const GAS_BUOYANCY: f64 = 0.3;
+
+/// The edge length of a square ```Chunk``` in cells
+pub const CHUNK_SIZE: i32 = 64;
+
+/// The furthest a single cell update may read or write away from the cell, see ```Space::update_chunks_parallel()```.
+/// Chunks are processed in a checkerboard, so this must not be larger than half of ```CHUNK_SIZE```
+pub const UPDATE_REACH: i32 = CHUNK_SIZE / 2;
+
 /// The ```CellType``` is the material of a cell
+/// # Functionality:
+/// It is a compact id into the ```MaterialRegistry```, which loads the materials from a file at startup. Any number of materials can be added there without touching the engine
 /// # Options:
-/// The materials are: ```Air```, ```Rock```, ```Wood```, ```Sand```, ```Gunpowder```, ```Water```, ```Oil```, ```Fire```, ```Lava```, ```Acid```
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub enum CellType {
-    Air,
-    Rock,
-    Water,
-    Sand,
-    Gravel,
-    Wood,
-    Steam,
-    Gunpowder,
-    Oil,
-    Lava,
-    Acid,
+/// The materials the engine itself relies on have fixed ids, see ```BUILTIN_MATERIALS```. These are: ```Air```, ```Rock```, ```Water```, ```Sand```, ```Gravel```, ```Wood```, ```Steam```, ```Gunpowder```,
+/// ```Oil```, ```Lava```, ```Acid```, ```Glass```, ```Ice```, ```Fire```, ```Smoke```, ```Ash```, ```ToxicGas```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CellType(pub u16);
+
+#[allow(dead_code)]
+impl CellType {
+    pub const AIR: CellType = CellType(0);
+    pub const ROCK: CellType = CellType(1);
+    pub const WATER: CellType = CellType(2);
+    pub const SAND: CellType = CellType(3);
+    pub const GRAVEL: CellType = CellType(4);
+    pub const WOOD: CellType = CellType(5);
+    pub const STEAM: CellType = CellType(6);
+    pub const GUNPOWDER: CellType = CellType(7);
+    pub const OIL: CellType = CellType(8);
+    pub const LAVA: CellType = CellType(9);
+    pub const ACID: CellType = CellType(10);
+    pub const GLASS: CellType = CellType(11);
+    pub const ICE: CellType = CellType(12);
+    pub const FIRE: CellType = CellType(13);
+    pub const SMOKE: CellType = CellType(14);
+    pub const ASH: CellType = CellType(15);
+    pub const TOXIC_GAS: CellType = CellType(16);
 }
 
 /// # Functionality:
 /// This enum dictates how the material is processed in the function that is responsible for updating the world
 /// # Options:
 /// The states of aggregation are: ```ImmovableSolid```, ```Granular```, ```Liquid```, ```Gas```, ```Replaceable```
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize)]
 pub enum StateOfAggregation {
     ImmovableSolid,
     Granular,
@@ -47,13 +149,57 @@ pub enum StateOfAggregation {
 /// This enum dictates what shape the ```paint_brush()``` function should assume
 /// # Options:
 /// The options of ```BrushType``` are: ```Square```, ```Circle```
+#[allow(dead_code)]
 pub enum BrushType {
     Square,
     Circle
 }
 
 /// # Functionality:
-/// This struct dictates the structure and information of the look-up-array ```CELL_PROPERTIES```, which inturn dictates material behavior. ```base_temp``` is measured in ```Kelvin```
+/// This enum dictates in which order ```Space::update_cell_behaviour()``` visits the cells of a tick
+/// # Options:
+/// ```TopDown``` goes row by row from the top left to the bottom right. ```BottomUp``` goes row by row from the bottom left to the top right.
+/// ```Alternating``` goes bottom up as well, but flips the direction of every other row and swaps them every generation.
+/// ```RandomPerChunk``` goes through the chunks one after another and visits the cells of each chunk in a random order
+/// # Behaviour:
+/// A fixed order lets the cells that are visited first take the free spots, so piles and puddles lean to one side. ```BottomUp``` is enough for piles, but only
+/// ```Alternating``` and ```RandomPerChunk``` keep puddles symmetric as well
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SweepOrder {
+    TopDown,
+    BottomUp,
+    #[default]
+    Alternating,
+    RandomPerChunk,
+}
+
+impl SweepOrder {
+
+    /// # Functionality:
+    /// Returns the order that comes after this one, so that they can be cycled through
+    pub fn next(self) -> SweepOrder {
+        match self {
+            SweepOrder::TopDown => SweepOrder::BottomUp,
+            SweepOrder::BottomUp => SweepOrder::Alternating,
+            SweepOrder::Alternating => SweepOrder::RandomPerChunk,
+            SweepOrder::RandomPerChunk => SweepOrder::TopDown,
+        }
+    }
+}
+
+/// # Functionality:
+/// This struct dictates the structure and information of the materials in the ```MaterialRegistry```, which inturn dictates material behavior. ```base_temp``` is measured in ```Kelvin```
+/// and ```temp_coefficient``` is the thermal conductivity that ```Space::update_cell_temperature()``` uses.
+/// ```burn_rate``` is the chance per tick and burning neighbour that a ```flammable``` cell catches fire, after which it turns into its ```burn_product```.
+/// ```explosive``` cells detonate instead of burning and ```hardness``` (0.0 to 1.0) is how much of a blast a cell can withstand.
+/// ```corrosion_resistance``` (0.0 to 1.0) lowers the chance of being dissolved by acid, at 1.0 a material is immune.
+/// ```dispersion``` is how many cells a liquid can slide sideways per tick and ```viscosity``` (0.0 to 1.0) is the chance that it does not move at all in a tick.
+/// A material with a ```lifetime``` lasts a random number of ticks within that inclusive range and then decays into ```decays_into```, see ```Space::update_lifetime()```.
+/// A solid with a ```support_strength``` crumbles into ```crumbles_into``` once it carries a larger load, solids without one never give way, see ```Space::update_structure()```.
+/// A granular material with a ```moisture_capacity``` soaks up water until it holds that much moisture and then turns into ```soaked_into```, if it has one, see ```Space::update_moisture()```.
+/// ```electric``` is the part a solid plays in a circuit, see ```Electric```. A switch turns into ```toggles_into```, the same switch in its other position, when it is flipped.
+/// A solid that ```emits``` keeps spawning fresh cells around itself and one that ```consumes``` destroys its neighbours, see ```Emission``` and ```Consumes```.
+/// Every fresh cell is up to ```color_variation``` (0.0 to 1.0) lighter or darker than ```base_color```, painted cells also get the ```texture``` of their material, see ```Cell::textured()```
 /// # Structure:
 /// ```
 /// pub struct CellTypeProperties {
@@ -61,8 +207,30 @@ pub enum BrushType {
 ///     pub cell_type: CellType,
 ///     pub state: StateOfAggregation,
 ///     pub density: f64,
+///     pub hardness: f32,
+///     pub corrosion_resistance: f32,
 ///     pub temp_coefficient: f32,
 ///     pub flammable: bool,
+///     pub explosive: bool,
+///     pub burn_rate: f32,
+///     pub burn_product: CellType,
+///     pub base_temp: u16,
+///     pub upper_phase: Option<PhaseTransition>,
+///     pub lower_phase: Option<PhaseTransition>,
+///     pub dispersion: u8,
+///     pub viscosity: f32,
+///     pub lifetime: Option<(u16, u16)>,
+///     pub decays_into: CellType,
+///     pub support_strength: Option<u16>,
+///     pub crumbles_into: CellType,
+///     pub moisture_capacity: u8,
+///     pub soaked_into: Option<CellType>,
+///     pub electric: Electric,
+///     pub toggles_into: Option<CellType>,
+///     pub emits: Option<Emission>,
+///     pub consumes: Consumes,
+///     pub color_variation: f32,
+///     pub texture: Texture,
 ///     pub base_color: [u8; 4]
 /// }
 /// ```
@@ -72,48 +240,111 @@ pub struct CellTypeProperties {
     pub cell_type: CellType,
     pub state: StateOfAggregation,
     pub density: f32,
+    pub hardness: f32,
+    pub corrosion_resistance: f32,
     pub temp_coefficient: f32,
     pub flammable: bool,
+    pub explosive: bool,
+    pub burn_rate: f32,
+    pub burn_product: CellType,
     pub base_temp: u16,
+    pub upper_phase: Option<PhaseTransition>,
+    pub lower_phase: Option<PhaseTransition>,
+    pub dispersion: u8,
+    pub viscosity: f32,
+    pub lifetime: Option<(u16, u16)>,
+    pub decays_into: CellType,
+    pub support_strength: Option<u16>,
+    pub crumbles_into: CellType,
+    pub moisture_capacity: u8,
+    pub soaked_into: Option<CellType>,
+    pub electric: Electric,
+    pub toggles_into: Option<CellType>,
+    pub emits: Option<Emission>,
+    pub consumes: Consumes,
+    pub color_variation: f32,
+    pub texture: Texture,
     pub base_color: [u8; 4]
 }
 
 /// # Functionality:
-/// This is the look-up-array for other functions to rely on
+/// Describes the change into another ```CellType``` once a temperature threshold is crossed. ```upper_phase``` covers melting and boiling, ```lower_phase``` covers freezing, condensing and solidifying
+/// # Behaviour:
+/// The transition behaves like latent heat. A heated cell only changes once it is ```latent_heat``` above ```temp``` and the new cell is ```latent_heat``` colder.
+/// A cooled cell only changes once it is ```latent_heat``` below ```temp``` and the new cell is ```latent_heat``` warmer. Two materials that share a ```temp``` therefore never flicker between each other
+/// # Structure:
+/// ```
+/// pub struct PhaseTransition {
+///     pub temp: u16,
+///     pub into: CellType,
+///     pub latent_heat: u16,
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PhaseTransition {
+    pub temp: u16,
+    pub into: CellType,
+    pub latent_heat: u16,
+}
+
+impl PhaseTransition {
+    pub const fn at(temp: u16, into: CellType, latent_heat: u16) -> Option<PhaseTransition> { Some(PhaseTransition { temp, into, latent_heat }) }
+}
+
+/// # Functionality:
+/// This struct describes a single alchemical reaction between two neighbouring cells. The reactions are defined next to the materials in the ```MaterialRegistry```
+/// # Behaviour:
+/// When a cell of ```reactants.0``` touches a cell of ```reactants.1``` they turn into ```products.0``` and ```products.1``` with a chance of ```probability``` per tick.
+/// The reaction only happens while the mean temperature of both cells is within the inclusive ```temp_range```. The products start at that mean temperature plus ```heat```,
+/// so a negative ```heat``` makes the reaction absorb heat
 /// # Structure:
-/// It's an static array of ```CellTypeProperties``` with fixed lenght
-static CELL_PROPERTIES: [CellTypeProperties; 11] = [    
-    CellTypeProperties { name: "Air",       cell_type: CellType::Air,       state: StateOfAggregation::Replaceable,     density: 0.0,   temp_coefficient: 1.0,      flammable: false, base_temp: 298,   base_color: [0,   0,    0, 0] },
-    CellTypeProperties { name: "Rock",      cell_type: CellType::Rock,      state: StateOfAggregation::ImmovableSolid,  density: 9.0,   temp_coefficient: 0.1,      flammable: false, base_temp: 298,   base_color: [119, 136,  153, 255] },
-    CellTypeProperties { name: "Water",     cell_type: CellType::Water,     state: StateOfAggregation::Liquid,          density: 1.0,   temp_coefficient: 0.1,      flammable: false, base_temp: 298,   base_color: [0, 0,  255, 255] },
-    CellTypeProperties { name: "Sand",      cell_type: CellType::Sand,      state: StateOfAggregation::Granular,        density: 1.5,   temp_coefficient: 0.1,      flammable: false, base_temp: 298,   base_color: [250, 250,  210, 255] },
-    CellTypeProperties { name: "Gravel",    cell_type: CellType::Gravel,    state: StateOfAggregation::Granular,        density: 3.1,   temp_coefficient: 0.1,      flammable: false, base_temp: 298,   base_color: [112, 128,  144, 255] },
-    CellTypeProperties { name: "Wood",      cell_type: CellType::Wood,      state: StateOfAggregation::ImmovableSolid,  density: 1.2,   temp_coefficient: 0.1,      flammable: true,  base_temp: 298,   base_color: [139, 69,   19, 255] },
-    CellTypeProperties { name: "Steam",     cell_type: CellType::Steam,     state: StateOfAggregation::Gas,             density: 0.1,   temp_coefficient: 0.1,      flammable: false, base_temp: 298,   base_color: [206, 206,  209, 255] },
-    CellTypeProperties { name: "Gunpowder", cell_type: CellType::Gunpowder, state: StateOfAggregation::Granular,        density: 1.7,   temp_coefficient: 0.1,      flammable: true,  base_temp: 298,   base_color: [70, 70,    80, 255] },
-    CellTypeProperties { name: "Oil",       cell_type: CellType::Oil,       state: StateOfAggregation::Liquid,          density: 0.9,   temp_coefficient: 0.1,      flammable: true,  base_temp: 298,   base_color: [55, 58,    54, 255] },
-    CellTypeProperties { name: "Lava",      cell_type: CellType::Lava,      state: StateOfAggregation::Liquid,          density: 3.1,   temp_coefficient: 100.0,    flammable: false, base_temp: 298,   base_color: [255, 0,    0, 255] },
-    CellTypeProperties { name: "Acid",      cell_type: CellType::Acid,      state: StateOfAggregation::Liquid,          density: 1.4,   temp_coefficient: 0.1,      flammable: false, base_temp: 298,   base_color: [0,   255,  0, 255] },
-];
+/// ```
+/// pub struct Reaction {
+///     pub reactants: (CellType, CellType),
+///     pub products: (CellType, CellType),
+///     pub probability: f32,
+///     pub temp_range: (u16, u16),
+///     pub heat: i16,
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Reaction {
+    pub reactants: (CellType, CellType),
+    pub products: (CellType, CellType),
+    pub probability: f32,
+    pub temp_range: (u16, u16),
+    pub heat: i16,
+}
+
+impl Reaction {
+
+    /// # Functionality:
+    /// Returns the reaction between a and b together with a bool that is true if a and b are swapped relative to ```reactants```
+    pub fn find(a: CellType, b: CellType) -> Option<(&'static Reaction, bool)> { MaterialRegistry::get().find_reaction(a, b) }
+}
 
 impl CellTypeProperties {
 
     /// # Functionality:
-    /// This function returns a random element of the ```CELL_PROPERTIES``` array. It is not dependant on the size of ```CELL_PROPERTIES```
+    /// This function returns the properties of a random material in the ```MaterialRegistry```. It is not dependant on the number of materials
     /// # Panic behaviour:
-    /// Panics if the length of ```CELL_PROPERTIES < 1```
+    /// Panics if the registry holds no materials
     #[allow(dead_code)]
-    pub fn rand_cell_properties() -> CellTypeProperties{ CELL_PROPERTIES[rand::thread_rng().gen_range(0..CELL_PROPERTIES.len())] }
+    pub fn rand_cell_properties() -> CellTypeProperties {
+        let materials = MaterialRegistry::get().materials();
+        materials[rand::thread_rng().gen_range(0..materials.len())]
+    }
 
     /// # Functionality:
-    /// This is the highly imortant function that returns 
+    /// This is the highly imortant function that returns the properties of a material from the ```MaterialRegistry```
     /// # Panic behaviour:
-    /// Panics if the length of ```CELL_PROPERTIES < 1```
-    pub fn get_cell_properties<'a>(cell_type: CellType) -> &'a CellTypeProperties { &CELL_PROPERTIES[cell_type as usize] }
+    /// Panics if ```cell_type``` is not an id of the registry
+    pub fn get_cell_properties<'a>(cell_type: CellType) -> &'a CellTypeProperties { &MaterialRegistry::get().materials()[cell_type.0 as usize] }
 
     pub fn get_cell_by_number(selection: &usize) -> (CellType, &'static str) {
-        let a = selection % CELL_PROPERTIES.len();
-        (CELL_PROPERTIES[a].cell_type, &CELL_PROPERTIES[a].name)
+        let materials = MaterialRegistry::get().materials();
+        let a = selection % materials.len();
+        (materials[a].cell_type, materials[a].name)
     }
 }
 
@@ -123,29 +354,49 @@ impl CellTypeProperties {
 /// ```
 /// pub struct Cell {
 ///     pub cell_type: CellType,
+///     pub color: [u8; 4],
 ///     pub generation: u32,
-///     pub temp: f32,
+///     pub temp: u16,
+///     pub lifetime: u16,
+///     pub strength: u8,
+///     pub moisture: u8,
+///     pub charge: u8,
+///     pub emits: CellType,
+///     pub velocity: (f32, f32),
 ///}
 /// ```
+/// ```lifetime``` is the number of ticks a cell of a material with a ```lifetime```, like ```CellType::FIRE``` or ```CellType::STEAM```, has left before it decays and ```strength``` is how much a ```CellType::ACID``` cell can still dissolve.
+/// ```moisture``` is how much water a cell with a ```moisture_capacity``` has soaked up, see ```Space::update_moisture()```.
+/// ```charge``` is how many ticks ago a conductor was last hit by a spark, counted down from ```SPARK_LIFE```, or how long a logic gate still remembers its inputs, see ```Space::update_electricity()```.
+/// ```emits``` is the material a source spawns, it starts as the ```material``` of its ```Emission``` and changes when another material is painted onto the source, see ```Space::set_emission()```.
+/// ```velocity``` is the speed of a falling cell in cells per tick, across and along the gravity at its position, see ```Space::move_with_velocity()```
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Cell {
     pub cell_type: CellType,
     pub color: [u8; 4],
     pub generation: u32,
     pub temp: u16,
+    pub lifetime: u16,
+    pub strength: u8,
+    pub moisture: u8,
+    pub charge: u8,
+    pub emits: CellType,
+    pub velocity: (f32, f32),
 }
 
 impl Cell {
 
     /// # Functionality:
     /// sets the cell to be air
-    pub fn set_air() -> Cell { Cell { cell_type: CellType::Air, generation: 0, color: [0; 4], temp: 298 } }
+    pub fn set_air() -> Cell { Cell { cell_type: CellType::AIR, generation: 0, color: [0; 4], temp: AMBIENT_TEMP, lifetime: 0, strength: 0, moisture: 0, charge: 0, emits: CellType::AIR, velocity: (0.0, 0.0) } }
 
     /// # Functionality:
     /// returns the CellTypeProperties struct with respect to the CellType
     pub fn get_cell_properties<'a>(&self) -> &'a CellTypeProperties { CellTypeProperties::get_cell_properties(self.cell_type) }
 
-    pub fn build_cell(cell_type: CellType) -> Cell {
+    /// # Functionality:
+    /// Builds a fresh cell of ```cell_type```. Random starting values like the ```lifetime``` and the shade of its colour are drawn from ```rng```
+    pub fn build_cell(cell_type: CellType, rng: &mut impl Rng) -> Cell {
 
         //this is the cell properties that will be used to build the cell
         let ref_cell_properties = CellTypeProperties::get_cell_properties(cell_type);
@@ -154,8 +405,108 @@ impl Cell {
         Cell { 
             cell_type: ref_cell_properties.cell_type,
             generation: 0, 
-            color: ref_cell_properties.base_color, 
-            temp: ref_cell_properties.base_temp,  
+            color: match ref_cell_properties.color_variation {
+                0.0 => ref_cell_properties.base_color,
+                variation => shade(ref_cell_properties.base_color, 1.0 + rng.gen_range(-variation..=variation)),
+            },
+            temp: ref_cell_properties.base_temp,
+            lifetime: match ref_cell_properties.lifetime {
+                Some((min, max)) => rng.gen_range(min..=max),
+                None => 0,
+            },
+            strength: match cell_type {
+                CellType::ACID => ACID_STRENGTH,
+                _ => 0,
+            },
+            moisture: 0,
+            charge: 0,
+            emits: match ref_cell_properties.emits {
+                Some(emission) => emission.material,
+                None => CellType::AIR,
+            },
+            velocity: (0.0, 0.0),
+        }
+    }
+}
+
+/// # Functionality:
+/// An inclusive rectangle of cell coordinates
+/// # Structure:
+/// ```
+/// pub struct DirtyRect {
+///     pub min_x: i32,
+///     pub min_y: i32,
+///     pub max_x: i32,
+///     pub max_y: i32,
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DirtyRect {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+}
+
+impl DirtyRect {
+    /// # Functionality:
+    /// Returns the smallest rectangle that contains both ```self``` and ```other```
+    pub fn union(self, other: DirtyRect) -> DirtyRect {
+        DirtyRect {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    /// # Functionality:
+    /// Returns the overlap of ```self``` and ```other```, or ```None``` if they do not touch
+    pub fn intersection(self, other: DirtyRect) -> Option<DirtyRect> {
+        let rect = DirtyRect {
+            min_x: self.min_x.max(other.min_x),
+            min_y: self.min_y.max(other.min_y),
+            max_x: self.max_x.min(other.max_x),
+            max_y: self.max_y.min(other.max_y),
+        };
+        if rect.min_x > rect.max_x || rect.min_y > rect.max_y { return None }
+        Some(rect)
+    }
+}
+
+/// # Functionality:
+/// Keeps track of the activity in a ```CHUNK_SIZE * CHUNK_SIZE``` square of ```Space```
+/// # Structure:
+/// ```
+/// pub struct Chunk {
+///     pub bounds: DirtyRect,
+///     pub dirty: Option<DirtyRect>,
+///     pub next_dirty: Option<DirtyRect>,
+/// }
+/// ```
+/// ```bounds``` are the cells that belong to the chunk. ```dirty``` holds the cells that changed in the last tick and are updated in the current one,
+/// while ```next_dirty``` collects the changes of the current tick.
+/// A chunk where both are ```None``` is asleep and is skipped entirely until a change in or right next to it wakes it up again
+#[derive(Copy, Clone, Debug)]
+pub struct Chunk {
+    pub bounds: DirtyRect,
+    pub dirty: Option<DirtyRect>,
+    pub next_dirty: Option<DirtyRect>,
+}
+
+impl Chunk {
+    /// # Functionality:
+    /// Returns whether the chunk has to be simulated in the current tick
+    pub fn is_awake(&self) -> bool { self.dirty.is_some() || self.next_dirty.is_some() }
+
+    /// # Functionality:
+    /// Returns the cells that a pass has to visit, either all of them if ```whole_chunks``` is set or only the dirty ones. Returns ```None``` if the chunk is asleep
+    pub fn active_rect(&self, whole_chunks: bool) -> Option<DirtyRect> {
+        match (whole_chunks, self.dirty, self.next_dirty) {
+            (true, _, _) if self.is_awake() => Some(self.bounds),
+            (false, Some(dirty), Some(next)) => Some(dirty.union(next)),
+            (false, dirty, next) => dirty.or(next),
+            _ => None,
         }
     }
 }
@@ -170,22 +521,62 @@ impl Cell {
 ///     pub lenght: i32,
 ///     pub generation: u32,
 ///     pub cells: Vec<Cell>,
+///     pub chunks_x: i32,
+///     pub chunks: Vec<Chunk>,
+///     pub parallel: bool,
+///     pub sweep: SweepOrder,
+///     pub gravity: GravityLayer,
+///     pub particles: ParticleLayer,
+///     pub bodies: RigidBodyLayer,
+///     pub rng: SmallRng,
+///     pub world_offset: (i64, i64),
+///     pub simulated: DirtyRect,
+///     heat_delta: Vec<f32>,
+///     pending_explosions: Vec<usize>,
 /// }
 /// ```
+/// The cells are split into rows of ```chunks_x``` chunks of ```CHUNK_SIZE```, the chunks at the right and bottom edge may be smaller.
+/// Every pass only visits the cells of chunks that are awake, see ```Chunk```.
+/// If ```parallel``` is set, the movement and the alchemy are spread over all cores, see ```update_chunks_parallel()```. The movement visits the cells in the order of ```sweep```
+/// and every cell falls in the direction that ```gravity``` has at its position, see ```gravity_at()```.
+/// Cells that left the grid fly in ```particles``` until they settle again, groups of solid cells that lost their hold fall as a whole in ```bodies```.
+/// Every random decision of the simulation is drawn from ```rng```, so the same seed and the same inputs always lead to the same world, see ```with_seed()```.
+/// ```world_offset``` are the world coordinates of the cell at ```0, 0```, which textures are sampled with, see ```paint_cell()```.
+/// Only the cells inside of ```simulated``` can be marked as dirty, the chunks outside of it never wake up and stay frozen as they are, see ```World```
 pub struct Space {
     pub width: i32,
     pub height: i32,
     pub lenght: i32,
     pub generation: u32,
     pub cells: Vec<Cell>,
+    pub chunks_x: i32,
+    pub chunks: Vec<Chunk>,
+    pub parallel: bool,
+    pub sweep: SweepOrder,
+    pub gravity: GravityLayer,
+    pub particles: ParticleLayer,
+    pub bodies: RigidBodyLayer,
+    pub rng: SmallRng,
+    pub world_offset: (i64, i64),
+    pub simulated: DirtyRect,
+    heat_delta: Vec<f32>,
+    pending_explosions: Vec<usize>,
 }
 
 impl Space {
     /// # Functionality:
     /// Creates a new simulation space with dimentions ```width * height``` and thus an ```index i``` of ```width * height = lenght```
+    /// # Behaviour:
+    /// The ```rng``` gets a random seed, use ```with_seed()``` for a reproducible simulation
     /// # Panic behaviour:
     /// panics if ```width < 0``` or ```height < 0```
-    pub fn new(width: i32, height: i32) -> Self {
+    pub fn new(width: i32, height: i32) -> Self { Space::with_seed(width, height, rand::random()) }
+
+    /// # Functionality:
+    /// Creates a new simulation space like ```new()```, whose ```rng``` starts from ```seed```
+    /// # Panic behaviour:
+    /// panics if ```width < 0``` or ```height < 0```
+    pub fn with_seed(width: i32, height: i32, seed: u64) -> Self {
 
         // creates the cell 'list'
         let length = width * height;
@@ -195,7 +586,153 @@ impl Space {
         for _ in 0..length {
             cells.push(Cell::set_air());
         }
-        Space { width, height, lenght: width * height, generation: 0, cells }
+        let (chunks_x, chunks) = Space::build_chunks(width, height);
+
+        Space {
+            width,
+            height,
+            lenght: width * height,
+            generation: 0,
+            cells,
+            chunks_x,
+            chunks,
+            parallel: false,
+            sweep: SweepOrder::default(),
+            gravity: GravityLayer::default(),
+            particles: ParticleLayer::default(),
+            bodies: RigidBodyLayer::default(),
+            rng: SmallRng::seed_from_u64(seed),
+            world_offset: (0, 0),
+            simulated: DirtyRect { min_x: 0, min_y: 0, max_x: width - 1, max_y: height - 1 },
+            heat_delta: vec![0.0; length as usize],
+            pending_explosions: Vec::new(),
+        }
+    }
+
+    /// # Functionality:
+    /// Splits a space with the dimentions ```width * height``` into sleeping chunks and returns them together with the amount of chunks per row
+    /// # Behaviour:
+    /// Rounds up, so that every cell belongs to a chunk
+    fn build_chunks(width: i32, height: i32) -> (i32, Vec<Chunk>) {
+        let chunks_x = (width + CHUNK_SIZE - 1) / CHUNK_SIZE;
+        let chunks_y = (height + CHUNK_SIZE - 1) / CHUNK_SIZE;
+        let mut chunks = Vec::with_capacity((chunks_x * chunks_y) as usize);
+        for cy in 0..chunks_y {
+            for cx in 0..chunks_x {
+                let bounds = DirtyRect {
+                    min_x: cx * CHUNK_SIZE,
+                    min_y: cy * CHUNK_SIZE,
+                    max_x: ((cx + 1) * CHUNK_SIZE).min(width) - 1,
+                    max_y: ((cy + 1) * CHUNK_SIZE).min(height) - 1,
+                };
+
+                // an empty space has nothing to simulate
+                chunks.push(Chunk { bounds, dirty: None, next_dirty: None });
+            }
+        }
+        (chunks_x, chunks)
+    }
+
+    /// # Functionality:
+    /// Returns the index of the chunk that contains the cell at index i
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn get_chunk_index(&self, i: usize) -> usize {
+        let (x, y) = self.get_coordinates(i as isize);
+        ((x / CHUNK_SIZE) + (y / CHUNK_SIZE) * self.chunks_x) as usize
+    }
+
+    /// # Functionality:
+    /// Returns whether the chunk that contains the cell at index i is asleep
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn is_asleep(&self, i: usize) -> bool { !self.chunks[self.get_chunk_index(i)].is_awake() }
+
+    /// # Functionality:
+    /// Marks every cell in ```rect``` as changed, so that it is updated in the next tick
+    /// # Behaviour:
+    /// The rect is clipped to ```simulated``` and split up between the chunks it covers, every one of them is woken up
+    pub fn mark_dirty_rect(&mut self, rect: DirtyRect) {
+        let Some(rect) = rect.intersection(self.simulated) else { return };
+
+        for cy in (rect.min_y / CHUNK_SIZE)..=(rect.max_y / CHUNK_SIZE) {
+            for cx in (rect.min_x / CHUNK_SIZE)..=(rect.max_x / CHUNK_SIZE) {
+                let chunk = &mut self.chunks[(cx + cy * self.chunks_x) as usize];
+                let Some(part) = rect.intersection(chunk.bounds) else { continue };
+                chunk.next_dirty = Some(match chunk.next_dirty {
+                    Some(dirty) => dirty.union(part),
+                    None => part,
+                });
+            }
+        }
+    }
+
+    /// # Functionality:
+    /// Returns whether the cell at index i is inside of ```simulated```
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn is_simulated(&self, i: usize) -> bool {
+        let (x, y) = self.get_coordinates(i as isize);
+        self.simulated.intersection(DirtyRect { min_x: x, min_y: y, max_x: x, max_y: y }).is_some()
+    }
+
+    /// # Functionality:
+    /// Marks the cell at index i as changed
+    /// # Behaviour:
+    /// Its direct neighbours are marked as well, because they might be able to move now. This is what wakes up neighbouring chunks
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn mark_dirty(&mut self, i: usize) {
+        let (x, y) = self.get_coordinates(i as isize);
+        self.mark_dirty_rect(DirtyRect { min_x: x - 1, min_y: y - 1, max_x: x + 1, max_y: y + 1 });
+    }
+
+    /// # Functionality:
+    /// Returns the indices of the cells that need to be visited by a pass, in the order of the rows of the space
+    /// # Behaviour:
+    /// If ```whole_chunks``` is set every cell of an awake chunk is returned, otherwise only the cells inside of its dirty rects
+    pub fn active_cells(&self, whole_chunks: bool) -> Vec<usize> {
+        let mut cells = Vec::new();
+
+        for y in 0..self.height {
+            let cy = y / CHUNK_SIZE;
+            for cx in 0..self.chunks_x {
+                let Some(rect) = self.chunks[(cx + cy * self.chunks_x) as usize].active_rect(whole_chunks) else { continue };
+                if y < rect.min_y || y > rect.max_y { continue }
+
+                let row = (y * self.width) as usize;
+                cells.extend((rect.min_x..=rect.max_x).map(|x| row + x as usize));
+            }
+        }
+        cells
+    }
+
+    /// # Functionality:
+    /// Returns ```cells``` in the order of ```sweep```, see ```SweepOrder```
+    /// # Behaviour:
+    /// ```cells``` have to be in the order of the rows of the space, like ```active_cells()``` returns them. ```Alternating``` flips a row if the sum of its y coordinate and the generation is odd
+    pub fn sweep_cells(&mut self, cells: &[usize]) -> Vec<usize> {
+        let mut cells = cells.to_vec();
+        let width = self.width as usize;
+        let generation = self.generation as usize;
+
+        match self.sweep {
+            SweepOrder::TopDown => {},
+            SweepOrder::BottomUp => cells.sort_by_key(|&i| (std::cmp::Reverse(i / width), i % width)),
+            SweepOrder::Alternating => cells.sort_by_key(|&i| {
+                let (x, y) = (i % width, i / width);
+                (std::cmp::Reverse(y), if (y + generation) % 2 == 1 { width - x } else { x })
+            }),
+            SweepOrder::RandomPerChunk => {
+                let mut by_chunk: Vec<(usize, usize)> = cells.iter().map(|&i| (self.get_chunk_index(i), i)).collect();
+                by_chunk.sort_by_key(|&(chunk, _)| chunk);
+                for chunk in by_chunk.chunk_by_mut(|a, b| a.0 == b.0) {
+                    chunk.shuffle(&mut self.rng);
+                }
+                cells = by_chunk.into_iter().map(|(_, i)| i).collect();
+            },
+        }
+        cells
     }
 
     /// # Functionality:
@@ -248,7 +785,7 @@ impl Space {
     /// # Functionality:
     /// Returns the ```Option<isize>``` where ```Some()``` is the checked index ```i```
     pub fn get_index_checked(&self, x: i32, y: i32) -> Result<isize, CustomErrors> {
-        if x > self.width || y > self.height || x < 0 || y < 0 {
+        if x >= self.width || y >= self.height || x < 0 || y < 0 {
             Err(CustomErrors::OutOfBounds)
         } else {
             Ok(self.get_index(x, y))
@@ -259,11 +796,10 @@ impl Space {
     /// Checks if the index is within bounds. returns ```false``` if the index is not inbounds
     /// # Formula:
     /// ```
-    /// i > 0 && i < self.lenght as isize - 1
-    /// return true;
+    /// i >= 0 && i < self.lenght as isize
     /// ```
     pub fn index_inbounds(&self, i: isize) -> bool {
-        i > 0 && i < self.lenght as isize
+        i >= 0 && i < self.lenght as isize
     }
 
     /// # Functionality:
@@ -272,7 +808,7 @@ impl Space {
     /// Return ```CustomErrors::OutOfBounds``` if the function fails
     pub fn get_properties_checked(&self, i: isize) -> Result<&CellTypeProperties, CustomErrors> {
         if !self.index_inbounds(i) { return Err(CustomErrors::OutOfBounds) }
-        Ok(&self.cells[i as usize].get_cell_properties())
+        Ok(self.cells[i as usize].get_cell_properties())
     }
 
     /// # Functionality:
@@ -307,6 +843,10 @@ impl Space {
         //mark all cells as updated
         self.cells[jj].generation = self.generation;
         self.cells[ii].generation = self.generation;
+
+        // both cells changed, so their chunks have to keep going
+        self.mark_dirty(ii);
+        self.mark_dirty(jj);
     }
 
     /// # Functionality:
@@ -315,14 +855,15 @@ impl Space {
     /// May cause a cell to wait too long to update, due to ```self.cells[i].generation = self.generation```
     /// # Panic behaviour:
     /// Panics if ```self.index_inbounds(i) == false``` or ```self.index_inbounds(j) == false```
-    #[allow(dead_code)]
     pub fn set_cell(&mut self, i: usize, cell: &Cell) {
         
         // replace the cell
+        self.note_removed_solid(i, cell);
         self.cells[i] = *cell;
 
         //mark it as updated
         self.cells[i].generation = self.generation;
+        self.mark_dirty(i);
     }
     
     /// # Functionality:
@@ -337,10 +878,12 @@ impl Space {
         if !self.index_inbounds(i) { return Err(CustomErrors::OutOfBounds) }
 
         // replace the cell
+        self.note_removed_solid(i as usize, cell);
         self.cells[i as usize] = *cell;
 
         //mark it as updated
         self.cells[i as usize].generation = self.generation;
+        self.mark_dirty(i as usize);
 
         // mark it as done
         Ok(true)
@@ -358,35 +901,60 @@ impl Space {
 
                 // get the index of the mouse position
                 let i = self.get_index(x + dx, y + dy);
+                if !self.index_inbounds(i) { continue }
 
-                // this handles the cell material
-                let cell = &Cell::build_cell(brush_material);
-
-                match (self.index_inbounds(i), &brush_type) {
-
-                    // ignore all cases where the index is out of bounds
-                    (false, _) => false,
-
-                    //standard square brush
-                    (true, BrushType::Square) => self.set_cell_checked(i,cell).unwrap_or(false),
+                // the circle brush leaves out the corners of the square, before a cell is built for them
+                if matches!(brush_type, BrushType::Circle) && dx*dx + dy*dy > brush_radius*brush_radius { continue }
 
-                    // i don't know why but this is the best way to do it, because for some reason the compiler doesn't allow for it to be written as dx^2 or dx.abs()^2
-                    (true, BrushType::Circle) => { if dx*dx + dy*dy <= brush_radius*brush_radius { self.set_cell_checked(i,cell).unwrap_or(false) } else { false } },
-                };
+                // this handles the cell material
+                let cell = &self.paint_cell(i as usize, brush_material);
+                self.set_cell_checked(i, cell).ok();
             }
         }
     }
 
     /// # Functionality:
-    /// Simulates the movement of all cells in ```Space```
+    /// Simulates the movement of all cells in the dirty rects of ```Space```
     /// # Behaviour:
+    /// Starts a new tick: the changes of the last tick become the dirty rects of this one and chunks without changes go to sleep, see ```Chunk```.
+    /// The particles and rigid bodies are moved after the cells, see ```update_particles()``` and ```update_rigid_bodies()```.
+    /// Around every solid that was removed, the terrain is checked for groups that lost their hold and for cells that carry too much, see ```detach_floating_solids()``` and ```update_structure()```.
+    /// Solids that were removed outside of ```simulated``` are kept in ```removed_solids``` until they are simulated.
     /// Matches behavior to ```CellType``` and executes matching functions. If the cell is of the ```StateOfAggregation::ImmovableSolid``` or ```StateOfAggregation::Replaceable``` type, then it will be skipped
     /// # Panic behaviour:
     /// Inherits the panic behavior of all used functions and thus can make it hard to track down errors
     pub fn update_cell_behaviour(&mut self) {
 
-        // iterate trough all elements of the Vec
-        for i in 0..(self.lenght as usize) {
+        // the changes of the last tick decide what is updated in this one, chunks without any go to sleep
+        for chunk in &mut self.chunks {
+            chunk.dirty = chunk.next_dirty.take();
+        }
+
+        if self.parallel {
+            self.update_chunks_parallel(false, Space::behaviour_pass);
+        } else {
+            let cells = self.active_cells(false);
+            self.behaviour_pass(&cells);
+        }
+        self.update_particles();
+
+        // the solids that were removed in this tick decide where the terrain may have lost its hold, frozen ones have to wait
+        let (removed, frozen): (Vec<usize>, Vec<usize>) = std::mem::take(&mut self.bodies.removed_solids).into_iter().partition(|&i| self.is_simulated(i));
+        self.bodies.removed_solids = frozen;
+        self.detach_floating_solids(&removed);
+        self.update_structure(&removed);
+        self.update_rigid_bodies();
+
+        // mark the space as updated and allow it to be updated again in the next iteration
+        self.increment_generation()
+    }
+
+    /// # Functionality:
+    /// Moves every cell in ```cells``` according to its ```StateOfAggregation```, in the order of ```sweep```
+    /// # Panic behaviour:
+    /// Panics if one of the indices is out of bounds
+    pub fn behaviour_pass(&mut self, cells: &[usize]) {
+        for i in self.sweep_cells(cells) {
 
             // needs to check if the cell needs updating
             if self.cell_needs_updating(i) {
@@ -395,13 +963,13 @@ impl Space {
                 match self.cells[i].get_cell_properties().state {
 
                     // uses move_granular() to simulate sand/gravel like materials
-                    StateOfAggregation::Granular => self.move_granular(i as isize, true, false),
+                    StateOfAggregation::Granular => self.move_granular(i as isize, self.gravity_at(i)),
 
                     // move_granular() exept it can move left and right
-                    StateOfAggregation::Liquid => self.move_liquid(i as isize, true, true),
+                    StateOfAggregation::Liquid => self.move_liquid(i as isize, self.gravity_at(i)),
 
                     // baisically just reverse liquid
-                    StateOfAggregation::Gas => self.move_gas(i as isize, false, true),
+                    StateOfAggregation::Gas => self.move_gas(i as isize, self.gravity_at(i).opposite()),
 
                     // discard all else
                     _ => false,
@@ -409,212 +977,1422 @@ impl Space {
 
                 // mark the cell as updated
                 self.update_cell_generation(i);   
+            } else {
+
+                // the cell was changed in this generation and has to wait for the next one
+                self.mark_dirty(i);
             }
         }
-        
-        // mark the space as updated and allow it to be updated again in the next iteration
-        self.increment_generation()
     }
 
     /// # Functionality:
-    /// Tries to move a cell vertically. Returns a sucess bool
+    /// Runs ```pass``` over the active cells of all chunks on every core, ```whole_chunks``` is handed to ```active_cells()```
     /// # Behaviour:
-    /// Depending on the ```gravity_normal``` bool it moves it up or down. If ```gravity_normal``` is set to true gravity is normal
-    /// # Undefined behaviour:
-    /// If ```i < 0 || i > self.lenght``` then it might cause unwanted behavior. Not really 'undefined', but usually unwanted
-    pub fn try_move_vert(&mut self, i: isize, gravity_normal: bool, density_based: bool) -> Result<bool, CustomErrors> {
-
-        // turns the gravity_normal bool into something more usable
-        let j = if gravity_normal { i + self.width as isize } else { i - self.width as isize };
+    /// The chunks are processed in four phases like the fields of a checkerboard, so that two chunks of the same phase are always one chunk apart.
+    /// Every chunk gets its own copy of the cells within ```UPDATE_REACH``` around it, which can not overlap the copy of any other chunk of that phase.
+    /// After a phase the copies are written back, together with their dirty rects and explosions, before the next phase starts. Every cell is still updated at most once per tick
+    /// # Panic behaviour:
+    /// Any update that reaches further than ```UPDATE_REACH``` from its cell may panic or be lost
+    pub fn update_chunks_parallel(&mut self, whole_chunks: bool, pass: fn(&mut Space, &[usize])) {
+        for phase in 0..4 {
+            let mut tasks = Vec::new();
+
+            for c in 0..self.chunks.len() {
+                let chunk = self.chunks[c];
+                let (cx, cy) = (c as i32 % self.chunks_x, c as i32 / self.chunks_x);
+                if cx % 2 != phase % 2 || cy % 2 != phase / 2 { continue }
+                let Some(rect) = chunk.active_rect(whole_chunks) else { continue };
+
+                let window = DirtyRect {
+                    min_x: (chunk.bounds.min_x - UPDATE_REACH).max(0),
+                    min_y: (chunk.bounds.min_y - UPDATE_REACH).max(0),
+                    max_x: (chunk.bounds.max_x + UPDATE_REACH).min(self.width - 1),
+                    max_y: (chunk.bounds.max_y + UPDATE_REACH).min(self.height - 1),
+                };
+                let window_width = window.max_x - window.min_x + 1;
+
+                // the cells of the chunk in the coordinates of its window, in the same order as active_cells()
+                let mut cells = Vec::new();
+                for y in rect.min_y..=rect.max_y {
+                    let row = ((y - window.min_y) * window_width) as usize;
+                    cells.extend((rect.min_x..=rect.max_x).map(|x| row + (x - window.min_x) as usize));
+                }
+                // every window draws from its own rng, seeded in chunk order so that the result does not depend on the scheduling
+                let seed = self.rng.gen();
+                tasks.push((window, self.copy_window(window, seed), cells));
+            }
 
-        // check if the index is inbounds
-        if !self.index_inbounds(j) { return Err(CustomErrors::OutOfBounds) }
-        
-        // checks if the cell is not solid
-        if !self.is_solid(j)? {
+            let results: Vec<(DirtyRect, Space)> = tasks.into_par_iter().map(|(window, mut space, cells)| {
+                pass(&mut space, &cells);
+                (window, space)
+            }).collect();
 
-            // checks if the swap is density based and checks if the index i has a higher density than the index j
-            if density_based && self.compare_density(i, j)? {
-                self.swap_cells(i, j);
-                return Ok(true);
-            }
-            // another boolean check to ensure that the code is only executed if the cell is not density based 
-            if !density_based {
-                self.swap_cells(i, j);
-                return Ok(true);
+            for (window, space) in results {
+                self.paste_window(window, space);
             }
         }
-
-        // if the code gets here, then this is undefined behavior
-        Err(CustomErrors::UndefinedBehavior)
     }
-    
-    /// # Functionality:
-    /// Checks if a cell is air or if the cell is not an ```ImmovableSolid```, and then it compares ```densities```
-    /// # Structure:
-    /// test for ```index_inbounds()``` && ```boundary detetection``` && ```non-ImmovableSolid```. 
-    /// Then test for ```index_inbounds``` && ```density difference```
-    pub fn compare_sides(&mut self, ref_cell: isize, i: isize) -> [bool; 4] {
 
-        // these are the indices of the cells that will be checked
-        let left_pos = i - 1;
-        let right_pos = i + 1;
-
-        // check if the cell is not solid
-        let left = !self.is_solid(left_pos).unwrap_or(true);
-        let right = !self.is_solid(right_pos).unwrap_or(true);
-
-        // compare the density of the cells ref_cell and right_pos/left_pos
-        let left_less_dense = self.compare_density(ref_cell, left_pos).unwrap_or(false);
-        let right_less_dense = self.compare_density(ref_cell, right_pos).unwrap_or(false);
-
-        // return
-        [left, left_less_dense, right, right_less_dense]
-    }
-    
-    pub fn random_move_side(&mut self, can_move: [bool; 2], i: isize, j: isize) -> Result<bool, CustomErrors> {
-        // random bool to decide the direction of movement
-        let rand_bool = rand::random::<bool>();
-
-        // series of checks to move the cell
-        // TODO: make this more robust and more efficient (i.e. reduce complexity)
-        if rand_bool {
-            if can_move[0] {
-                self.swap_cells(i, j - 1);
-                return Ok(true);
-            }
-            else if can_move[1] {
-                self.swap_cells(i, j + 1);
-                return Ok(true);
-            }
-        }
-        else if can_move[1] {
-            self.swap_cells(i, j + 1);
-            return Ok(true);
+    /// # Functionality:
+    /// Copies the cells inside of ```rect``` into a new ```Space``` of the same generation, where ```rect.min_x, rect.min_y``` becomes ```0, 0```. Its ```rng``` starts from ```seed```
+    /// # Behaviour:
+    /// All chunks of the copy are asleep and it has no heat buffer, so ```update_cell_temperature()``` must not be called on it
+    /// # Panic behaviour:
+    /// Panics if ```rect``` is not inside of the space
+    fn copy_window(&self, rect: DirtyRect, seed: u64) -> Space {
+        let width = rect.max_x - rect.min_x + 1;
+        let height = rect.max_y - rect.min_y + 1;
+
+        let mut cells = Vec::with_capacity((width * height) as usize);
+        for y in rect.min_y..=rect.max_y {
+            let row = self.get_index(rect.min_x, y) as usize;
+            cells.extend_from_slice(&self.cells[row..row + width as usize]);
         }
-        else if can_move[0] {
-            self.swap_cells(i, j - 1);
-            return Ok(true);
+        let (chunks_x, chunks) = Space::build_chunks(width, height);
+
+        Space {
+            width,
+            height,
+            lenght: width * height,
+            generation: self.generation,
+            cells,
+            chunks_x,
+            chunks,
+            parallel: false,
+            sweep: self.sweep,
+            gravity: self.gravity.translated((-rect.min_x, -rect.min_y)),
+            particles: ParticleLayer::default(),
+            bodies: RigidBodyLayer::default(),
+            rng: SmallRng::seed_from_u64(seed),
+            world_offset: (self.world_offset.0 + rect.min_x as i64, self.world_offset.1 + rect.min_y as i64),
+            simulated: DirtyRect { min_x: 0, min_y: 0, max_x: width - 1, max_y: height - 1 },
+            heat_delta: Vec::new(),
+            pending_explosions: Vec::new(),
         }
-
-        // throw an error if this code is reached
-        Err(CustomErrors::OutOfBounds)
     }
 
     /// # Functionality:
-    /// Checks if a cell is air or if the cell is not an ```ImmovableSolid```, and then it compares densities
-    /// # Structure:
-    /// first checks if cells to the left and right and the ones below and afterwards checks for density if ```density_based == true```
-    pub fn try_move_diagonally(&mut self, i: isize, gravity_normal: bool, density_based: bool) -> Result<bool, CustomErrors> {
-
-        // turns the gravity_normal bool into something more usable
-        let j = if gravity_normal { i + self.width as isize } else { i - self.width as isize };
+    /// Writes a copy made by ```copy_window(rect)``` back into the space, including the cells it marked as dirty, the explosions it set off and the solids it removed
+    /// # Panic behaviour:
+    /// Panics if ```rect``` is not inside of the space
+    fn paste_window(&mut self, rect: DirtyRect, window: Space) {
+        for y in 0..window.height {
+            let row = self.get_index(rect.min_x, rect.min_y + y) as usize;
+            let window_row = (y * window.width) as usize;
+            self.cells[row..row + window.width as usize].copy_from_slice(&window.cells[window_row..window_row + window.width as usize]);
+        }
 
-        // handle index i and j. Check self.index_inbounds(i) and self.index_inbounds(j)
-        if !self.index_inbounds(i) || !self.index_inbounds(j) { return Err(CustomErrors::OutOfBounds) }
+        let offset = |(x, y): (i32, i32)| self.get_index(rect.min_x + x, rect.min_y + y) as usize;
+        let explosions: Vec<usize> = window.pending_explosions.iter().map(|j| offset(window.get_coordinates(*j as isize))).collect();
+        let removed: Vec<usize> = window.bodies.removed_solids.iter().map(|j| offset(window.get_coordinates(*j as isize))).collect();
+        self.pending_explosions.extend(explosions);
+        self.bodies.removed_solids.extend(removed);
+        self.particles.particles.extend(window.particles.particles.iter().map(|particle| Particle {
+            position: (particle.position.0 + rect.min_x as f32, particle.position.1 + rect.min_y as f32),
+            ..*particle
+        }));
+
+        for dirty in window.chunks.iter().filter_map(|chunk| chunk.next_dirty) {
+            self.mark_dirty_rect(DirtyRect {
+                min_x: dirty.min_x + rect.min_x,
+                min_y: dirty.min_y + rect.min_y,
+                max_x: dirty.max_x + rect.min_x,
+                max_y: dirty.max_y + rect.min_y,
+            });
+        }
+    }
 
-        // some logic processing
-        let same_level_array = self.compare_sides(i, i);
-        let offset_level_array = self.compare_sides(i, j);
-        let a = compare_arrays_4(same_level_array, offset_level_array);
-        
-        // merges the boolean arrays based on the density_based bool
-        let can_move = if density_based { [a[0] && a[1], a[2] && a[3]]} else {[a[0], a[2]]};
+    /// # Functionality:
+    /// This is the displacement model that all movement goes through. Returns whether the cell at index i may take the place of the cell at index j,
+    /// where ```rising``` says whether i moves against gravity
+    /// # Behaviour:
+    /// ```ImmovableSolid``` and ```Replaceable``` cells never move by themselves. Nothing can move into ```ImmovableSolid``` or ```Granular``` cells, which is what lets piles form,
+    /// while anything that moves takes the place of ```Replaceable``` cells like air. Between liquids and gases the density decides:
+    /// a cell sinks into lighter cells and rises into heavier ones. See ```displacement_chance()``` for how fast that happens
+    pub fn can_displace(&self, i: isize, j: isize, rising: bool) -> bool {
+        let (Ok(mover), Ok(target)) = (self.get_properties_checked(i), self.get_properties_checked(j)) else { return false };
+
+        match (mover.state, target.state) {
+            (StateOfAggregation::ImmovableSolid | StateOfAggregation::Replaceable, _) => false,
+            (_, StateOfAggregation::ImmovableSolid | StateOfAggregation::Granular) => false,
+            (_, StateOfAggregation::Replaceable) => true,
+            _ if rising => self.compare_density(j, i).unwrap_or(false),
+            _ => self.compare_density(i, j).unwrap_or(false),
+        }
+    }
 
-        // swap based on rand_bool to randomise the resulting swap
-        self.random_move_side(can_move, i, j)?;
-        
-        //if anyting errors then this is undefined behavior
-        Err(CustomErrors::UndefinedBehavior)
+    /// # Functionality:
+    /// Returns the chance per tick that the cell at index i actually moves through the cell at index j, if ```can_displace()``` allows it
+    /// # Formula:
+    /// ```
+    /// (a - b).abs() / a.max(b)
+    /// ```
+    /// ```Replaceable``` cells are always displaced. Otherwise a small density difference means slow sinking or rising, so sand sinks through oil faster than through water
+    pub fn displacement_chance(&self, i: isize, j: isize) -> f64 {
+        let (Ok(mover), Ok(target)) = (self.get_properties_checked(i), self.get_properties_checked(j)) else { return 0.0 };
+        if target.state == StateOfAggregation::Replaceable { return 1.0 }
+
+        let (a, b) = (mover.density as f64, target.density as f64);
+        if a.max(b) <= 0.0 { return 0.0 }
+        (a - b).abs() / a.max(b)
     }
 
     /// # Functionality:
-    /// Checks if a cell is air or if the cell is not an ```ImmovableSolid```, and then it compares densities
-    /// # Structure:
-    /// checks if cells to the left and right and randomly selects a order of execution
-    pub fn try_move_sideways(&mut self, i: isize, density_based: bool) -> Result<bool, CustomErrors> {
+    /// Returns whether the cell at index i moves against gravity, which only gases do. Moving that way counts as rising for ```can_displace()```
+    fn is_buoyant(&self, i: isize) -> bool {
+        self.get_properties_checked(i).is_ok_and(|properties| properties.state == StateOfAggregation::Gas)
+    }
 
-        // create variables for further processing
-        let same_level = self.compare_sides(i, i);
+    /// # Functionality:
+    /// Returns whether a step from i in ```direction``` squeezes through a diagonal gap
+    /// # Behaviour:
+    /// Only diagonal steps can cut a corner. The step is blocked if one of the two cells it passes is solid, except for the one that lies in the direction of ```gravity```,
+    /// which is usually what the cell rests on
+    pub fn cuts_corner(&self, i: isize, direction: Gravity, gravity: Gravity) -> bool {
+        if !direction.is_diagonal() { return false }
+        let (dx, dy) = direction.offset();
+        let (x, y) = self.get_coordinates(i);
+
+        [(dx, 0), (0, dy)].into_iter()
+            .filter(|&offset| offset != gravity.offset())
+            .any(|(cx, cy)| self.get_index_checked(x + cx, y + cy).map_or(true, |corner| self.is_solid(corner).unwrap_or(true)))
+    }
 
-        // accounts for the density_based bool
-        let can_move = if density_based {
-            [same_level[0] && same_level[1], same_level[2] && same_level[3]]
+    /// # Functionality:
+    /// Tries to move a cell one step in the direction of ```gravity```. Returns a sucess bool
+    /// # Behaviour:
+    /// ```gravity``` is the direction the cell falls in, which is already turned around for gases, see ```is_buoyant()```.
+    /// A cell that may move but lost the roll of ```displacement_chance()``` returns false and keeps its chunk awake
+    /// # Panic behaviour:
+    /// Return ```CustomErrors::OutOfBounds``` if there is no cell in that direction and ```CustomErrors::CouldNotComplete``` if the cell in that direction blocks the way, see ```can_displace()``` and ```cuts_corner()```
+    pub fn try_move_vert(&mut self, i: isize, gravity: Gravity) -> Result<bool, CustomErrors> {
+        let j = self.get_neighbour(i, gravity).ok_or(CustomErrors::OutOfBounds)?;
+        if self.cuts_corner(i, gravity, gravity) || !self.can_displace(i, j, self.is_buoyant(i)) { return Err(CustomErrors::CouldNotComplete) }
+
+        // sinking through something that is almost as heavy takes a while
+        if self.rng.gen::<f64>() >= self.displacement_chance(i, j) {
+            self.mark_dirty(i as usize);
+            return Ok(false);
         }
-        else { 
-            [same_level[0], same_level[2]]
-        };
 
-        // swap based on rand_bool to randomise the resulting swap
-        self.random_move_side(can_move, i, i)?;
+        self.swap_cells(i, j);
+        Ok(true)
+    }
 
-        //if anyting errors then this is undefined behavior
-        Err(CustomErrors::UndefinedBehavior)
+    /// # Functionality:
+    /// Tries to move a cell one step diagonally to ```gravity```, to a random side first. Returns a sucess bool
+    /// # Behaviour:
+    /// The step must not squeeze through a diagonal gap, see ```cuts_corner()```. The target cell is checked with ```can_displace()``` and ```displacement_chance()```.
+    /// Under diagonal gravity one of the two steps runs along the floor, so it is tried even if there is nothing in the direction of ```gravity```
+    pub fn try_move_diagonally(&mut self, i: isize, gravity: Gravity) -> bool {
+        let rising = self.is_buoyant(i);
+
+        let first = if self.rng.gen::<bool>() { 1 } else { -1 };
+        for direction in [gravity.rotate(first), gravity.rotate(-first)] {
+            let Some(target) = self.get_neighbour(i, direction) else { continue };
+            if self.cuts_corner(i, direction, gravity) || !self.can_displace(i, target, rising) { continue }
+            if self.rng.gen::<f64>() >= self.displacement_chance(i, target) { continue }
+
+            self.swap_cells(i, target);
+            return true;
+        }
+        false
     }
 
     /// # Functionality:
     /// Simulates the movement of a cell assuming a state of aggregation of ```StateOfAggregation::Granular```
     /// # Behaviour:
-    /// Tries to mimic movement of granular materials by first checking below itself. And only if it can't move down it will try to move diagonally
+    /// Tries to mimic movement of granular materials by first falling with its velocity. And only if it can't move down it will try to move diagonally.
+    /// Wet cells often stay put instead of sliding, see ```cohesion()```
     /// # Structure:
-    /// First checks ```self.try_move_vert()``` and then ```self.try_move_diagonally()```
-    pub fn move_granular(&mut self, i: isize, gravity_normal: bool, density_based: bool) -> bool {
-        if self.try_move_vert(i, gravity_normal, density_based).unwrap_or(false) { return true }
-        self.try_move_diagonally(i, gravity_normal, density_based).unwrap_or(false)
+    /// First checks ```self.move_with_velocity()``` and then ```self.try_move_diagonally()```
+    pub fn move_granular(&mut self, i: isize, gravity: Gravity) -> bool {
+        if self.move_with_velocity(i, gravity) { return true }
+
+        // wet cells stick together and hold steeper slopes
+        if self.rng.gen::<f32>() < self.cohesion(i as usize) {
+            if self.could_slide(i, gravity) { self.mark_dirty(i as usize) }
+            return false;
+        }
+        self.try_move_diagonally(i, gravity)
     }
 
     /// # Functionality:
     /// Simulates the movement of a cell assuming a state of aggregation of ```StateOfAggregation::Liquid```
     /// # Behaviour:
-    /// Tries to mimic movement of liquid materials by first checking below itself. And only if it can't move down it will try to move diagonally. Should that too fail will it try to move sideways
+    /// Tries to mimic movement of liquid materials by first falling with its velocity. And only if it can't move down it will try to move diagonally. Should that too fail will it spread sideways.
+    /// A liquid with a ```viscosity``` skips ticks at random, so it oozes instead of flowing
     /// # Structure:
-    /// First checks ```self.try_move_vert()``` then ```self.try_move_diagonally()``` and then ```self.try_move_sideways()```
-    pub fn move_liquid(&mut self, i: isize, gravity_normal: bool, density_based: bool) -> bool {
-        if self.try_move_vert(i, gravity_normal, density_based).unwrap_or(false) { return true }
-        if self.try_move_diagonally(i, gravity_normal, density_based).unwrap_or(false) { return true }
-        self.try_move_sideways(i, density_based).unwrap_or(false)
+    /// First checks ```self.move_with_velocity()``` then ```self.try_move_diagonally()``` and then ```self.try_disperse()```
+    pub fn move_liquid(&mut self, i: isize, gravity: Gravity) -> bool {
+        let properties = self.cells[i as usize].get_cell_properties();
+        if properties.viscosity > 0.0 && self.rng.gen::<f32>() < properties.viscosity {
+
+            // a liquid that still has somewhere to go must not fall asleep while it waits
+            if self.could_move(i, gravity) { self.mark_dirty(i as usize) }
+            return false;
+        }
+
+        if self.move_with_velocity(i, gravity) { return true }
+        if self.try_move_diagonally(i, gravity) { return true }
+        self.try_disperse(i, properties.dispersion, gravity)
     }
 
     /// # Functionality:
-    /// Simulates the movement of a cell assuming a state of aggregation of ```StateOfAggregation::Gas```
+    /// Returns whether the cell at index i could move into the cell below or next to it, seen from ```gravity```, see ```can_displace()```
+    pub fn could_move(&self, i: isize, gravity: Gravity) -> bool {
+        let below = self.get_neighbour(i, gravity).is_some_and(|j| self.can_displace(i, j, self.is_buoyant(i)));
+        below || [gravity.rotate(2), gravity.rotate(-2)].into_iter().filter_map(|side| self.get_neighbour(i, side)).any(|j| self.can_displace(i, j, false))
+    }
+
+    /// # Functionality:
+    /// Slides the liquid cell at index i up to ```dispersion``` cells to a random side of ```gravity```. Returns a sucess bool
     /// # Behaviour:
-    /// Tries to mimic movement of liquid materials by first checking below itself. And only if it can't move up it will try to move diagonally. Should that too fail will it try to move sideways
-    /// # Structure:
-    /// First checks ```self.try_move_vert()``` then ```self.try_move_diagonally()``` and then ```self.try_move_sideways()```
-    pub fn move_gas(&mut self, i: isize, gravity_normal: bool, density_based: bool) -> bool {
-        if self.try_move_vert(i, gravity_normal, density_based).unwrap_or(false) { return true }
-        if self.try_move_diagonally(i, gravity_normal, density_based).unwrap_or(false) { return true }
-        self.try_move_sideways(i, density_based).unwrap_or(false)
+    /// The cell passes every cell that it can displace and stops in front of anything else or right above a gap, so that it falls into the gap in the next tick.
+    /// If it can not move to the chosen side it tries the other one
+    pub fn try_disperse(&mut self, i: isize, dispersion: u8, gravity: Gravity) -> bool {
+        let rising = self.is_buoyant(i);
+        let first = if self.rng.gen::<bool>() { 2 } else { -2 };
+
+        for side in [gravity.rotate(first), gravity.rotate(-first)] {
+            let mut j = i;
+            for _ in 0..dispersion.max(1) {
+                let Some(next) = self.get_neighbour(j, side) else { break };
+                if self.cuts_corner(j, side, gravity) || !self.can_displace(i, next, false) { break }
+                j = next;
+
+                if self.get_neighbour(j, gravity).is_some_and(|below| self.can_displace(i, below, rising)) { break }
+            }
+
+            if j != i {
+                self.swap_cells(i, j);
+                return true;
+            }
+        }
+        false
     }
 
     /// # Functionality:
-    /// This function is the backbone for all alchemical reactions
+    /// Lets the cell at index i fall in the direction of ```gravity``` with its ```velocity```, which builds up by ```GRAVITY``` every tick. Returns a sucess bool
     /// # Behaviour:
-    /// It matches the cell type of index i to it's corresponding behavior
-    pub fn update_cell_alchemy(&mut self) {
-        for i in 0..(self.lenght - 1) as usize {
-            match self.cells[i].cell_type {
+    /// The cell travels up to ```velocity.1``` cells along its path and stops in front of the first cell it can not pass, see ```try_move_vert()```.
+    /// A cell that only lost the roll to sink through something keeps its velocity and tries again in the next tick.
+    /// A cell that lands turns ```LANDING_SPREAD``` of its speed into sideways velocity in a random direction. It spends that sliding over the ground, slowed down by ```FRICTION```,
+    /// until it hits something or finds a gap to fall into. Liquids that land hard may splash up as a ```Particle``` instead, see ```SPLASH_CHANCE```
+    pub fn move_with_velocity(&mut self, i: isize, gravity: Gravity) -> bool {
+        let (mut velocity_x, falling) = self.cells[i as usize].velocity;
+        let velocity_y = (falling + GRAVITY).min(MAX_VELOCITY);
+        let rising = self.is_buoyant(i);
+
+        // fall until something is in the way
+        let mut j = i;
+        let mut blocked = false;
+        for _ in 0..(velocity_y as i32).max(1) {
+            let Some(next) = self.get_neighbour(j, gravity) else {
+                blocked = true;
+                break;
+            };
+            match self.try_move_vert(j, gravity) {
+                Ok(true) => j = next,
+                Ok(false) => break,
+                Err(_) => {
+                    blocked = true;
+                    break;
+                },
+            }
+        }
+        if j != i {
+            self.cells[j as usize].velocity = (velocity_x, velocity_y);
+            return true;
+        }
+
+        // sinking through something takes a few tries, that is no landing
+        if !blocked { return false }
 
-                // change the rng range for different probabilities
-                // ignore the safety checks since i is already in range
-                CellType::Steam => if rand::thread_rng().gen_range(1..=1250) < 2 { self.set_cell(i, &Cell { cell_type: CellType::Water, color: CellTypeProperties::get_cell_properties(CellType::Water).base_color, generation: 0, temp: 298 });},
+        // the cell landed, part of the impact pushes it sideways
+        if falling >= 1.0 {
+            let direction = if self.rng.gen::<bool>() { 1.0 } else { -1.0 };
+            velocity_x += direction * falling * LANDING_SPREAD;
 
-                // discard all else
-                _ => (),
+            // hard landing liquids splash up
+            let liquid = self.cells[j as usize].get_cell_properties().state == StateOfAggregation::Liquid;
+            if liquid && falling >= SPLASH_SPEED && self.rng.gen_bool(SPLASH_CHANCE) {
+                self.eject_cell(j as usize, gravity.to_grid((velocity_x, -falling * LANDING_SPREAD)));
+                return true;
             }
         }
+
+        let side = if velocity_x > 0.0 { gravity.rotate(-2) } else { gravity.rotate(2) };
+        for _ in 0..(velocity_x.abs() as i32) {
+            let Some(k) = self.get_neighbour(j, side).filter(|&k| !self.cuts_corner(j, side, gravity) && self.can_displace(j, k, false)) else {
+                velocity_x = 0.0;
+                break;
+            };
+            self.swap_cells(j, k);
+            j = k;
+
+            // stop sliding over an edge, the cell falls down from there in the next tick
+            if self.get_neighbour(j, gravity).is_some_and(|below| self.can_displace(j, below, rising)) { break }
+        }
+
+        velocity_x *= FRICTION;
+        if velocity_x.abs() < 1.0 { velocity_x = 0.0 }
+        self.cells[j as usize].velocity = (velocity_x, 0.0);
+        j != i
     }
-}
 
-/// # Functionality:
-/// made to reduce code duplication
-/// # Structure:
-/// compares the values individually and returns a boolean array based on the resulting values
-pub fn compare_arrays_4(a: [bool; 4], b: [bool; 4]) -> [bool; 4] {
-    [
-        a[0] && b[0],
-        a[1] && b[1],
-        a[2] && b[2],
-        a[3] && b[3]
-    ]
+    /// # Functionality:
+    /// Simulates the movement of a cell assuming a state of aggregation of ```StateOfAggregation::Gas```
+    /// # Behaviour:
+    /// With a chance of ```GAS_BUOYANCY``` the gas tries to rise through lighter cells first, see ```try_move_vert()```. Otherwise it drifts to a random neighbour, see ```try_diffuse()```.
+    /// This way gases spread out through a cave and only thin out towards the floor instead of stacking under the ceiling like an upside down liquid
+    pub fn move_gas(&mut self, i: isize, up: Gravity) -> bool {
+        if self.rng.gen_bool(GAS_BUOYANCY) && self.try_move_vert(i, up).unwrap_or(false) { return true }
+        self.try_diffuse(i, up)
+    }
+
+    /// # Functionality:
+    /// Tries to move a gas to one of its four neighbours seen from ```up```, picked at random. Returns a sucess bool
+    /// # Behaviour:
+    /// The gas moves into every cell that it can displace, see ```can_displace()```. Two different gases also mix with each other,
+    /// the more similar their density the more likely, so gases of about the same weight blend while very different ones stay layered.
+    /// A gas that did not move but has room around it keeps its chunk awake
+    pub fn try_diffuse(&mut self, i: isize, up: Gravity) -> bool {
+        let (direction, rising) = match self.rng.gen_range(0..4) {
+            0 => (up, true),
+            1 => (up.opposite(), false),
+            2 => (up.rotate(2), false),
+            _ => (up.rotate(-2), false),
+        };
+
+        let target = self.get_neighbour(i, direction).filter(|_| !self.cuts_corner(i, direction, up.opposite()));
+        if let Some(j) = target {
+            let moved = self.can_displace(i, j, rising) || (self.is_mixable(i, j) && self.rng.gen::<f64>() >= self.displacement_chance(i, j));
+            if moved {
+                self.swap_cells(i, j);
+                return true;
+            }
+        }
+
+        if self.could_diffuse(i) { self.mark_dirty(i as usize) }
+        false
+    }
+
+    /// # Functionality:
+    /// Returns whether the cells at index i and j are two different gases
+    pub fn is_mixable(&self, i: isize, j: isize) -> bool {
+        let (Ok(a), Ok(b)) = (self.get_properties_checked(i), self.get_properties_checked(j)) else { return false };
+        a.state == StateOfAggregation::Gas && b.state == StateOfAggregation::Gas && a.cell_type != b.cell_type
+    }
+
+    /// # Functionality:
+    /// Returns whether the gas at index i has a neighbour that it could drift into or mix with
+    pub fn could_diffuse(&self, i: isize) -> bool {
+        self.get_neighbours(i as usize).into_iter().flatten().map(|j| j as isize)
+            .any(|j| self.get_properties_checked(j).is_ok_and(|target| target.state == StateOfAggregation::Replaceable) || self.is_mixable(i, j))
+    }
+
+    /// # Functionality:
+    /// Simulates the heat transfer between all cells of awake chunks in ```Space```
+    /// # Behaviour:
+    /// Every cell exchanges heat with its right and lower neighbour. The exchanged amount depends on the temperature difference and the ```temp_coefficient``` of both cells,
+    /// where the harmonic mean makes the worse conductor the bottleneck. Afterwards every cell relaxes towards ```AMBIENT_TEMP```.
+    /// Cells of sleeping chunks only exchange heat with awake neighbours and wake up once their temperature changes
+    /// # Structure:
+    /// The changes are collected in ```heat_delta``` first and applied afterwards, so that the result does not depend on the update order
+    pub fn update_cell_temperature(&mut self) {
+
+        // the cells of awake chunks and their neighbours in sleeping ones, which the heat change of this tick is collected for in heat_delta
+        let awake = self.active_cells(true);
+        let mut border = Vec::new();
+
+        for &i in &awake {
+            let [up, down, left, right] = self.get_neighbours(i);
+
+            // look right and down, so that every pair is handled exactly once.
+            // Pairs with a sleeping cell to the left or above would be skipped that way, so those are handled from this side
+            let up = up.filter(|j| self.is_asleep(*j));
+            let left = left.filter(|j| self.is_asleep(*j));
+
+            for j in [right, down, up, left].into_iter().flatten() {
+                let flow = self.heat_exchange_rate(i, j) * (self.cells[j].temp as f32 - self.cells[i].temp as f32);
+                self.heat_delta[i] += flow;
+                self.heat_delta[j] -= flow;
+                if self.is_asleep(j) { border.push(j) }
+            }
+        }
+
+        for (i, relaxes) in awake.into_iter().map(|i| (i, true)).chain(border.into_iter().map(|i| (i, false))) {
+            let delta = std::mem::take(&mut self.heat_delta[i]);
+
+            // sleeping cells only take part in the exchange with their awake neighbours
+            let relaxation = if relaxes { (AMBIENT_TEMP as f32 - self.cells[i].temp as f32) * AMBIENT_RELAXATION } else { 0.0 };
+            let temp = (self.cells[i].temp as f32 + delta + relaxation).clamp(0.0, u16::MAX as f32);
+
+            // round stochastically, otherwise the small per tick changes would always round away and nothing would ever cool down completely
+            let rounded = (if self.rng.gen::<f32>() < temp.fract() { temp.ceil() } else { temp.floor() }) as u16;
+            if rounded != self.cells[i].temp {
+                self.cells[i].temp = rounded;
+                self.mark_dirty(i);
+            }
+        }
+    }
+
+    /// # Functionality:
+    /// Returns the fraction of the temperature difference between i and j that is exchanged per tick
+    /// # Formula:
+    /// ```
+    /// let harmonic_mean = 2.0 * a * b / (a + b);
+    /// (harmonic_mean * HEAT_EXCHANGE_RATE).min(MAX_HEAT_EXCHANGE)
+    /// ```
+    pub fn heat_exchange_rate(&self, i: usize, j: usize) -> f32 {
+        let a = self.cells[i].get_cell_properties().temp_coefficient;
+        let b = self.cells[j].get_cell_properties().temp_coefficient;
+
+        // two perfect insulators do not exchange anything
+        if a + b <= 0.0 { return 0.0 }
+        (2.0 * a * b / (a + b) * HEAT_EXCHANGE_RATE).min(MAX_HEAT_EXCHANGE)
+    }
+
+    /// # Functionality:
+    /// Replaces the cell at index i with a fresh cell of ```cell_type``` that has the temperature ```temp```
+    /// # Behaviour:
+    /// Unlike ```set_cell()``` the generation of the old cell is kept, so the new cell is not held back from moving
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn convert_cell(&mut self, i: usize, cell_type: CellType, temp: u16) {
+        let cell = Cell { generation: self.cells[i].generation, temp, ..Cell::build_cell(cell_type, &mut self.rng) };
+        self.note_removed_solid(i, &cell);
+        self.cells[i] = cell;
+        self.mark_dirty(i);
+    }
+
+    /// # Functionality:
+    /// Changes the cell at index i into another ```CellType``` if its temperature crossed one of its ```PhaseTransition``` thresholds. Returns a sucess bool
+    /// # Behaviour:
+    /// The latent heat is subtracted when the cell melts or boils and added when it freezes, condenses or solidifies, see ```PhaseTransition```
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn try_phase_transition(&mut self, i: usize) -> bool {
+        let temp = self.cells[i].temp;
+        let properties = self.cells[i].get_cell_properties();
+
+        if let Some(upper) = properties.upper_phase {
+            if temp >= upper.temp.saturating_add(upper.latent_heat) {
+                self.convert_cell(i, upper.into, temp - upper.latent_heat);
+                return true;
+            }
+        }
+
+        if let Some(lower) = properties.lower_phase {
+            if temp <= lower.temp.saturating_sub(lower.latent_heat) {
+                self.convert_cell(i, lower.into, temp + lower.latent_heat);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// # Functionality:
+    /// Returns the indices of the cells above, below, left and right of i. Neighbours that are out of bounds are ```None```
+    /// # Behaviour:
+    /// Unlike ```i - 1``` and ```i + 1``` this does not wrap around into the previous or next row
+    pub fn get_neighbours(&self, i: usize) -> [Option<usize>; 4] {
+        let (x, y) = self.get_coordinates(i as isize);
+        let width = self.width as usize;
+        [
+            if y > 0 { Some(i - width) } else { None },
+            if y + 1 < self.height { Some(i + width) } else { None },
+            if x > 0 { Some(i - 1) } else { None },
+            if x + 1 < self.width { Some(i + 1) } else { None },
+        ]
+    }
+
+    /// # Functionality:
+    /// Sets the ```flammable``` cell at index i on fire
+    /// # Behaviour:
+    /// The cell turns into ```CellType::FIRE``` and sheds its ```burn_product``` into a random neighbouring air cell, so that ash can fall and smoke can rise away from the flame.
+    /// ```explosive``` cells detonate instead once the alchemy pass is done, see ```explode()```
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn ignite(&mut self, i: usize) {
+
+        // an explosion reaches too far to happen in the middle of a chunk update
+        if self.cells[i].get_cell_properties().explosive { return self.pending_explosions.push(i) }
+
+        let product = self.cells[i].get_cell_properties().burn_product;
+        let temp = self.cells[i].temp.max(CellTypeProperties::get_cell_properties(CellType::FIRE).base_temp);
+        self.convert_cell(i, CellType::FIRE, temp);
+
+        if product == CellType::AIR { return }
+
+        // the product can only go where there is free space
+        let free: Vec<usize> = self.get_neighbours(i).into_iter().flatten().filter(|j| self.cells[*j].cell_type == CellType::AIR).collect();
+        if !free.is_empty() {
+            let j = free[self.rng.gen_range(0..free.len())];
+            self.convert_cell(j, product, temp);
+        }
+    }
+
+    /// # Functionality:
+    /// Returns the indices of all cells of ```cell_type``` that are connected to i through their direct neighbours, including i itself
+    /// # Behaviour:
+    /// Stops collecting after ```limit``` cells
+    pub fn connected_cells(&self, i: usize, cell_type: CellType, limit: usize) -> Vec<usize> {
+        let mut found = vec![i];
+        let mut visited = vec![false; self.lenght as usize];
+        visited[i] = true;
+
+        // depth first flood fill, found doubles as the result
+        let mut stack = vec![i];
+        while let Some(j) = stack.pop() {
+            for k in self.get_neighbours(j).into_iter().flatten() {
+                if found.len() >= limit { return found }
+                if !visited[k] && self.cells[k].cell_type == cell_type {
+                    visited[k] = true;
+                    found.push(k);
+                    stack.push(k);
+                }
+            }
+        }
+        found
+    }
+
+    /// # Functionality:
+    /// Detonates the ```explosive``` cell at index i together with every explosive cell of the same type that is connected to it
+    /// # Behaviour:
+    /// The charge is consumed and the blast radius grows with the square root of its size, up to ```MAX_BLAST_RADIUS```.
+    /// Every cell in the radius is hit with a power that falls off from 1.0 at the center to 0.0 at the edge, see ```blast_cell()```.
+    /// The surroundings are heated up to ```BLAST_HEAT_REACH``` times the radius, which is also how other charges close by are set off in the following ticks
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn explode(&mut self, i: usize) {
+        let charge = self.connected_cells(i, self.cells[i].cell_type, MAX_CHARGE);
+
+        // the blast originates from the center of the charge
+        let (mut sum_x, mut sum_y) = (0, 0);
+        for j in &charge {
+            let (x, y) = self.get_coordinates(*j as isize);
+            sum_x += x as i64;
+            sum_y += y as i64;
+            self.convert_cell(*j, CellType::AIR, self.cells[*j].temp);
+        }
+        let center_x = (sum_x / charge.len() as i64) as i32;
+        let center_y = (sum_y / charge.len() as i64) as i32;
+
+        let radius = (BASE_BLAST_RADIUS + (charge.len() as f32).sqrt() * BLAST_RADIUS_PER_CHARGE).min(MAX_BLAST_RADIUS);
+        let reach = (radius * BLAST_HEAT_REACH).ceil() as i32;
+        self.mark_dirty_rect(DirtyRect { min_x: center_x - reach, min_y: center_y - reach, max_x: center_x + reach, max_y: center_y + reach });
+
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let (x, y) = (center_x + dx, center_y + dy);
+                if x < 0 || y < 0 || x >= self.width || y >= self.height { continue }
+
+                let j = self.get_index(x, y) as usize;
+                let distance = ((dx * dx + dy * dy) as f32).sqrt();
+
+                // heat everything in reach, the blast itself only hits what is inside the radius
+                let heat = BLAST_HEAT * (1.0 - distance / (radius * BLAST_HEAT_REACH)).max(0.0);
+                self.cells[j].temp = self.cells[j].temp.saturating_add(heat as u16);
+                if distance <= radius {
+                    self.blast_cell(j, (dx, dy), 1.0 - distance / radius);
+                }
+            }
+        }
+    }
+
+    /// # Functionality:
+    /// Applies a blast with ```power``` to the cell at index j, that lies ```offset``` away from the center of the blast
+    /// # Behaviour:
+    /// Air and gases are blown away and cells that are at least as hard as the power survive. Other ```explosive``` cells are only primed by the heat so that they go off one after another.
+    /// Softer granular and liquid cells are thrown outwards as debris, every other cell is destroyed. Cleared cells catch fire with a chance of ```BLAST_FIRE_CHANCE```
+    /// # Panic behaviour:
+    /// Panics if ```j > self.lenght```
+    pub fn blast_cell(&mut self, j: usize, offset: (i32, i32), power: f32) {
+        let properties = self.cells[j].get_cell_properties();
+        let temp = self.cells[j].temp;
+
+        match properties.state {
+            StateOfAggregation::Replaceable | StateOfAggregation::Gas => (),
+            _ if properties.explosive || properties.hardness >= power => return,
+            StateOfAggregation::Granular | StateOfAggregation::Liquid => self.throw_debris(j, offset, power),
+            StateOfAggregation::ImmovableSolid => (),
+        }
+
+        let remains = if self.rng.gen_bool(BLAST_FIRE_CHANCE) { CellType::FIRE } else { CellType::AIR };
+        self.convert_cell(j, remains, temp);
+    }
+
+    /// # Functionality:
+    /// Throws the cell at index j outwards along the line from the blast center as a ```Particle```
+    /// # Behaviour:
+    /// The debris flies faster the more ```power``` the blast had at its position, cells at the very center are thrown upwards
+    /// # Panic behaviour:
+    /// Panics if ```j > self.lenght```
+    pub fn throw_debris(&mut self, j: usize, offset: (i32, i32), power: f32) {
+        let distance = ((offset.0 * offset.0 + offset.1 * offset.1) as f32).sqrt();
+        let direction = if distance > 0.0 { (offset.0 as f32 / distance, offset.1 as f32 / distance) } else { (0.0, -1.0) };
+        let speed = DEBRIS_SPEED * power;
+        self.eject_cell(j, (direction.0 * speed, direction.1 * speed));
+    }
+
+    /// # Functionality:
+    /// Simulates a ```CellType::FIRE``` cell at index i
+    /// # Behaviour:
+    /// Fire stays as hot as its ```base_temp``` while it burns and heats its surroundings that way. Every ```flammable``` neighbour catches fire with the chance of its ```burn_rate```.
+    /// Fire that found fuel burns ```FIRE_FUEL_BONUS``` ticks longer, otherwise it burns out into its ```decays_into``` after its ```lifetime```, see ```update_lifetime()```
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn update_fire(&mut self, i: usize) {
+        self.cells[i].temp = self.cells[i].temp.max(self.cells[i].get_cell_properties().base_temp);
+
+        // a fire changes every tick until it is out
+        self.mark_dirty(i);
+
+        for j in self.get_neighbours(i).into_iter().flatten() {
+            let properties = self.cells[j].get_cell_properties();
+            if properties.flammable && self.rng.gen::<f32>() < properties.burn_rate {
+                self.ignite(j);
+                self.cells[i].lifetime = self.cells[i].lifetime.saturating_add(FIRE_FUEL_BONUS);
+            }
+        }
+    }
+
+    /// # Functionality:
+    /// Counts down the ```lifetime``` of the cell at index i, if its material has one. Returns whether the cell decayed
+    /// # Behaviour:
+    /// A cell with a ```lifetime``` keeps its chunk awake while it counts down. Once the count is at 0 the cell turns into its ```decays_into``` at the base temperature of that material,
+    /// so steam condenses into water, smoke thins out into air and fire burns out into smoke
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn update_lifetime(&mut self, i: usize) -> bool {
+        let properties = self.cells[i].get_cell_properties();
+        if properties.lifetime.is_none() { return false }
+
+        if self.cells[i].lifetime == 0 {
+            let into = properties.decays_into;
+            self.convert_cell(i, into, CellTypeProperties::get_cell_properties(into).base_temp);
+            return true;
+        }
+        self.cells[i].lifetime -= 1;
+        self.mark_dirty(i);
+        false
+    }
+
+    /// # Functionality:
+    /// Simulates a ```CellType::ACID``` cell at index i
+    /// # Behaviour:
+    /// Picks a random neighbour and dissolves it with a chance of ```ACID_REACTIVITY``` scaled down by its ```corrosion_resistance```.
+    /// Every dissolved cell costs ```ACID_COST``` strength and may release ```CellType::TOXIC_GAS```. Acid that used up its strength is diluted into water
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn update_acid(&mut self, i: usize) {
+        let neighbours: Vec<usize> = self.get_neighbours(i).into_iter().flatten().collect();
+        if neighbours.is_empty() { return }
+
+        // stay awake while there is something left to dissolve, even if it takes a few tries
+        if neighbours.iter().any(|&j| self.cells[j].get_cell_properties().corrosion_resistance < 1.0) { self.mark_dirty(i) }
+
+        let j = neighbours[self.rng.gen_range(0..neighbours.len())];
+        let resistance = self.cells[j].get_cell_properties().corrosion_resistance;
+        if self.rng.gen::<f32>() >= ACID_REACTIVITY * (1.0 - resistance) { return }
+
+        let remains = if self.rng.gen_bool(ACID_GAS_CHANCE) { CellType::TOXIC_GAS } else { CellType::AIR };
+        self.convert_cell(j, remains, self.cells[j].temp);
+
+        self.cells[i].strength = self.cells[i].strength.saturating_sub(ACID_COST);
+        if self.cells[i].strength == 0 {
+            self.convert_cell(i, CellType::WATER, self.cells[i].temp);
+        }
+    }
+
+    /// # Functionality:
+    /// Lets the cell at index i react with its right or lower neighbour according to the reactions of the ```MaterialRegistry```. Returns a sucess bool
+    /// # Behaviour:
+    /// Only the right and lower neighbour are checked, so that every pair of cells is evaluated once per tick. See ```Reaction``` for the rules
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn try_reaction(&mut self, i: usize) -> bool {
+        let [_, down, _, right] = self.get_neighbours(i);
+
+        for j in [right, down].into_iter().flatten() {
+            let Some((reaction, swapped)) = Reaction::find(self.cells[i].cell_type, self.cells[j].cell_type) else { continue };
+
+            let temp = ((self.cells[i].temp as u32 + self.cells[j].temp as u32) / 2) as u16;
+            if temp < reaction.temp_range.0 || temp > reaction.temp_range.1 { continue }
+
+            // a possible reaction keeps the chunk awake until it happens
+            self.mark_dirty(i);
+            if self.rng.gen::<f32>() >= reaction.probability { continue }
+
+            let (product_i, product_j) = if swapped { (reaction.products.1, reaction.products.0) } else { reaction.products };
+            let temp = (temp as i32 + reaction.heat as i32).clamp(0, u16::MAX as i32) as u16;
+            self.convert_cell(i, product_i, temp);
+            self.convert_cell(j, product_j, temp);
+            return true;
+        }
+        false
+    }
+
+    /// # Functionality:
+    /// This function is the backbone for all alchemical reactions
+    /// # Behaviour:
+    /// First counts down the ```lifetime``` of every cell of the awake chunks, see ```update_lifetime()```, then checks it for a phase transition, then for a reaction from the ```MaterialRegistry``` and then matches the cell type of index i to it's corresponding behavior.
+    /// ```flammable``` cells that got hotter than ```IGNITION_TEMP``` catch fire on their own and cells with a ```moisture_capacity``` get wet or dry out, see ```update_moisture()```.
+    /// Sources spawn their material and drains and the void eat their neighbours, see ```update_emitter()``` and ```update_drain()```. Afterwards the sparks move on through the circuits, see ```update_electricity()```. Ignited ```explosive``` cells detonate at the end of the pass
+    pub fn update_cell_alchemy(&mut self) {
+        if self.parallel {
+            self.update_chunks_parallel(true, Space::alchemy_pass);
+        } else {
+            let cells = self.active_cells(true);
+            self.alchemy_pass(&cells);
+        }
+        self.update_electricity();
+
+        // the explosions go off one after another, a charge may have been used up by an earlier one
+        for i in std::mem::take(&mut self.pending_explosions) {
+            if self.cells[i].get_cell_properties().explosive { self.explode(i) }
+        }
+    }
+
+    /// # Functionality:
+    /// Runs the alchemy of every cell in ```cells```, in the given order
+    /// # Behaviour:
+    /// See ```update_cell_alchemy()```. Explosions are only collected and have to be set off afterwards
+    /// # Panic behaviour:
+    /// Panics if one of the indices is out of bounds
+    pub fn alchemy_pass(&mut self, cells: &[usize]) {
+        for &i in cells {
+            if self.update_lifetime(i) || self.try_phase_transition(i) || self.try_reaction(i) { continue }
+
+            match self.cells[i].cell_type {
+
+                // fire burns down and spreads to its neighbours
+                CellType::FIRE => self.update_fire(i),
+
+                // acid eats into everything that is not resistant enough
+                CellType::ACID => self.update_acid(i),
+
+                // hot fuel ignites without an open flame, absorbent cells soak up water and sources and drains make and destroy matter
+                _ => {
+                    let properties = self.cells[i].get_cell_properties();
+                    if properties.flammable && self.cells[i].temp >= IGNITION_TEMP {
+                        self.mark_dirty(i);
+                        if self.rng.gen::<f32>() < properties.burn_rate { self.ignite(i) }
+                    }
+                    if properties.moisture_capacity > 0 { self.update_moisture(i) }
+                    if properties.emits.is_some() { self.update_emitter(i) }
+                    if properties.consumes != Consumes::Nothing { self.update_drain(i) }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cell_at, count, find, place, tick};
+
+    /// builds a column that is one cell wide, ```layers``` are given from top to bottom
+    fn column(layers: &[(CellType, usize)]) -> Space {
+        let height = layers.iter().map(|(_, count)| count).sum::<usize>();
+        let mut space = Space::with_seed(1, height as i32, 0);
+
+        let mut i = 0;
+        for &(cell_type, count) in layers {
+            for _ in 0..count {
+                let cell = Cell::build_cell(cell_type, &mut space.rng);
+                space.set_cell(i, &cell);
+                i += 1;
+            }
+        }
+        space
+    }
+
+    /// runs the movement for ```ticks``` and returns the order of the layers from top to bottom. Gases drift through air, so air is left out
+    fn settle(space: &mut Space, ticks: usize) -> Vec<CellType> {
+        for _ in 0..ticks { space.update_cell_behaviour() }
+
+        let mut layers: Vec<CellType> = Vec::new();
+        for cell in space.cells.iter().filter(|cell| cell.cell_type != CellType::AIR) {
+            if layers.last() != Some(&cell.cell_type) { layers.push(cell.cell_type) }
+        }
+        layers
+    }
+
+    #[test]
+    fn oil_floats_on_water() {
+        let mut space = column(&[(CellType::WATER, 6), (CellType::OIL, 6)]);
+        assert_eq!(settle(&mut space, 500), [CellType::OIL, CellType::WATER]);
+    }
+
+    #[test]
+    fn sand_sinks_through_water() {
+        let mut space = column(&[(CellType::SAND, 6), (CellType::WATER, 6)]);
+        assert_eq!(settle(&mut space, 500), [CellType::WATER, CellType::SAND]);
+    }
+
+    #[test]
+    fn steam_rises_through_water_and_air() {
+        let mut space = column(&[(CellType::AIR, 4), (CellType::WATER, 4), (CellType::STEAM, 4)]);
+        assert_eq!(settle(&mut space, 300), [CellType::STEAM, CellType::WATER]);
+    }
+
+    #[test]
+    fn mixed_column_settles_in_density_order() {
+        let mut space = column(&[
+            (CellType::SAND, 3), (CellType::STEAM, 3), (CellType::WATER, 3),
+            (CellType::AIR, 3), (CellType::OIL, 3), (CellType::STEAM, 3),
+        ]);
+        assert_eq!(settle(&mut space, 350), [CellType::STEAM, CellType::OIL, CellType::WATER, CellType::SAND]);
+    }
+
+    #[test]
+    fn granular_cells_do_not_sink_through_each_other() {
+        let mut space = column(&[(CellType::GRAVEL, 4), (CellType::SAND, 4)]);
+        assert_eq!(settle(&mut space, 200), [CellType::GRAVEL, CellType::SAND]);
+    }
+
+    #[test]
+    fn steam_condenses_once_its_lifetime_is_over() {
+        let mut space = column(&[(CellType::AIR, 3), (CellType::STEAM, 1)]);
+        let (_, max) = CellTypeProperties::get_cell_properties(CellType::STEAM).lifetime.expect("steam has a lifetime");
+
+        // the count goes down to 0 and the cell decays in the tick after
+        for _ in 0..max as usize + 1 {
+            space.update_cell_behaviour();
+            space.update_cell_alchemy();
+        }
+
+        assert!(space.cells.iter().all(|cell| cell.cell_type != CellType::STEAM));
+        assert_eq!(space.cells.iter().filter(|cell| cell.cell_type == CellType::WATER).count(), 1);
+    }
+
+    #[test]
+    fn fire_burns_out_into_smoke_once_its_lifetime_is_over() {
+        let mut space = Space::with_seed(3, 3, 0);
+        let fire = Cell::build_cell(CellType::FIRE, &mut space.rng);
+        space.set_cell(4, &fire);
+
+        // without any fuel around it the fire lasts exactly its lifetime
+        for _ in 0..fire.lifetime {
+            space.update_cell_alchemy();
+            assert_eq!(space.cells[4].cell_type, CellType::FIRE);
+        }
+        space.update_cell_alchemy();
+        assert_eq!(space.cells[4].cell_type, CellType::SMOKE);
+    }
+
+    #[test]
+    fn gases_spread_out_instead_of_stacking() {
+        let mut space = Space::with_seed(16, 16, 0);
+        for i in 0..16 {
+            let cell = Cell::build_cell(CellType::TOXIC_GAS, &mut space.rng);
+            space.set_cell(i, &cell);
+        }
+        for _ in 0..300 { space.update_cell_behaviour() }
+
+        let rows: Vec<usize> = (0..space.cells.len()).filter(|&i| space.cells[i].cell_type == CellType::TOXIC_GAS).map(|i| i / 16).collect();
+        assert_eq!(rows.len(), 16);
+        assert!(rows.iter().any(|&row| row > 0));
+    }
+
+    #[test]
+    fn similar_gases_mix() {
+        let mut space = column(&[(CellType::SMOKE, 4), (CellType::TOXIC_GAS, 4)]);
+        let mut swapped = false;
+        for _ in 0..100 {
+            space.update_cell_behaviour();
+            swapped |= space.cells[4].cell_type == CellType::SMOKE;
+        }
+        assert!(swapped);
+    }
+
+    /// runs every pass for ```ticks``` on a scene with falling, burning, reacting and exploding materials that spans several chunks
+    fn run_scene(seed: u64, parallel: bool, ticks: usize) -> Space {
+        let mut space = Space::with_seed(160, 160, seed);
+        space.parallel = parallel;
+
+        let materials = [CellType::SAND, CellType::WATER, CellType::OIL, CellType::LAVA, CellType::WOOD, CellType::GUNPOWDER, CellType::ACID, CellType::STEAM, CellType::FIRE];
+        for y in 20..100 {
+            for x in 10..150 {
+                let cell = Cell::build_cell(materials[((x / 12 + y / 9) % materials.len() as i32) as usize], &mut space.rng);
+                space.set_cell(space.get_index(x, y) as usize, &cell);
+            }
+        }
+
+        for _ in 0..ticks {
+            space.update_cell_behaviour();
+            space.update_cell_temperature();
+            space.update_cell_alchemy();
+        }
+        space
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_world() {
+        for parallel in [false, true] {
+            let a = run_scene(7, parallel, 150);
+            let b = run_scene(7, parallel, 150);
+            assert!(a.cells == b.cells, "the cells differ with parallel set to {parallel}");
+            assert!(a.particles.particles == b.particles.particles, "the particles differ with parallel set to {parallel}");
+        }
+    }
+
+    #[test]
+    fn different_seeds_give_different_worlds() {
+        assert!(run_scene(1, false, 50).cells != run_scene(2, false, 50).cells);
+    }
+
+    /// draws the space as text, one character per cell, so that a failed test shows what the pile or puddle looked like
+    fn render(space: &Space) -> String {
+        space.cells.chunks(space.width as usize).map(|row| {
+            row.iter().map(|cell| match cell.get_cell_properties().state {
+                StateOfAggregation::Granular => '#',
+                StateOfAggregation::Liquid => '~',
+                StateOfAggregation::Replaceable => ' ',
+                _ => '?',
+            }).collect::<String>()
+        }).collect::<Vec<_>>().join("\n")
+    }
+
+    /// returns how many cells of ```cell_type``` are left and right of the middle column
+    fn halves(space: &Space, cell_type: CellType) -> (usize, usize) {
+        let middle = space.width as usize / 2;
+        let xs = space.cells.iter().enumerate().filter(|(_, cell)| cell.cell_type == cell_type).map(|(i, _)| i % space.width as usize);
+        xs.fold((0, 0), |(left, right), x| (left + (x < middle) as usize, right + (x > middle) as usize))
+    }
+
+    /// pours sand onto the middle of the floor one cell per tick and lets the pile settle
+    fn pour_pile(sweep: SweepOrder, seed: u64) -> Space {
+        let mut space = Space::with_seed(65, 40, seed);
+        space.sweep = sweep;
+        for tick in 0..500 {
+            if tick < 300 && space.cells[32].cell_type == CellType::AIR {
+                let cell = Cell::build_cell(CellType::SAND, &mut space.rng);
+                space.set_cell(32, &cell);
+            }
+            space.update_cell_behaviour();
+        }
+        space
+    }
+
+    /// drops a block of water onto the middle of the floor and lets it spread out
+    fn drop_puddle(sweep: SweepOrder, seed: u64) -> Space {
+        let mut space = Space::with_seed(65, 20, seed);
+        space.sweep = sweep;
+        for y in 0..12 {
+            for x in 28..=36 {
+                let cell = Cell::build_cell(CellType::WATER, &mut space.rng);
+                space.set_cell(space.get_index(x, y) as usize, &cell);
+            }
+        }
+        for _ in 0..60 { space.update_cell_behaviour() }
+        space
+    }
+
+    /// checks that the cells on both sides of the middle add up to about the same amount over ```seeds``` runs
+    fn assert_symmetric(sweeps: &[SweepOrder], seeds: u64, build: fn(SweepOrder, u64) -> Space, cell_type: CellType) {
+        for &sweep in sweeps {
+            let (mut left, mut right) = (0, 0);
+            let mut last = String::new();
+            for seed in 0..seeds {
+                let space = build(sweep, seed);
+                let (l, r) = halves(&space, cell_type);
+                (left, right) = (left + l, right + r);
+                last = render(&space);
+            }
+            let imbalance = left.abs_diff(right) as f64 / (left + right) as f64;
+            assert!(imbalance < 0.05, "{sweep:?} leans to one side, {left} cells on the left and {right} on the right:\n{last}");
+        }
+    }
+
+    #[test]
+    fn sand_piles_are_symmetric() {
+        assert_symmetric(&[SweepOrder::BottomUp, SweepOrder::Alternating, SweepOrder::RandomPerChunk], 24, pour_pile, CellType::SAND);
+    }
+
+    // a liquid keeps sliding within its row, so a row that is always visited from the same side lets the puddle lean, even bottom up.
+    // A single puddle still sloshes to one side or the other, so it takes a lot of them to average that out
+    #[test]
+    fn puddles_are_symmetric() {
+        assert_symmetric(&[SweepOrder::Alternating, SweepOrder::RandomPerChunk], 96, drop_puddle, CellType::WATER);
+    }
+
+    #[test]
+    fn larger_density_difference_sinks_faster() {
+        let space = column(&[(CellType::SAND, 1), (CellType::WATER, 1), (CellType::OIL, 1), (CellType::AIR, 1)]);
+        assert!(space.displacement_chance(0, 2) > space.displacement_chance(0, 1));
+        assert_eq!(space.displacement_chance(0, 3), 1.0);
+        assert!(!space.can_displace(1, 2, true));
+    }
+
+    #[test]
+    fn bounds_checks_cover_exactly_the_grid() {
+        let space = Space::with_seed(4, 3, 0);
+
+        // the last row and column are inside, one past them is not
+        assert_eq!(space.get_index_checked(3, 2).ok(), Some(11));
+        assert_eq!(space.get_index_checked(0, 0).ok(), Some(0));
+        for (x, y) in [(4, 0), (0, 3), (4, 3), (-1, 0), (0, -1)] {
+            assert!(matches!(space.get_index_checked(x, y), Err(CustomErrors::OutOfBounds)), "({x}, {y})");
+        }
+
+        // the first and last index are inside
+        assert!(space.index_inbounds(0));
+        assert!(space.index_inbounds(11));
+        assert!(!space.index_inbounds(-1));
+        assert!(!space.index_inbounds(12));
+    }
+
+    /// builds a row of hot and cold cells of ```cell_type``` from the temperatures ```temps```
+    fn heat_row(cell_type: CellType, temps: &[u16]) -> Space {
+        let mut space = Space::with_seed(temps.len() as i32, 1, 0);
+        for (i, &temp) in temps.iter().enumerate() {
+            let cell = Cell { temp, ..Cell::build_cell(cell_type, &mut space.rng) };
+            space.set_cell(i, &cell);
+        }
+        space
+    }
+
+    #[test]
+    fn heat_flows_from_hot_to_cold_cells() {
+        let mut space = heat_row(CellType::ROCK, &[1000, AMBIENT_TEMP, AMBIENT_TEMP]);
+        space.update_cell_temperature();
+
+        // in one tick the heat only reaches the direct neighbour
+        assert!(space.cells[0].temp < 1000);
+        assert!(space.cells[1].temp > AMBIENT_TEMP);
+        assert_eq!(space.cells[2].temp, AMBIENT_TEMP);
+
+        // over time the row evens out to within a few degrees
+        for _ in 0..500 { space.update_cell_temperature() }
+        let temps: Vec<u16> = space.cells.iter().map(|cell| cell.temp).collect();
+        assert!(temps.iter().max().unwrap() - temps.iter().min().unwrap() <= 20, "{temps:?}");
+        assert!(temps.iter().all(|&temp| temp > AMBIENT_TEMP && temp < 600), "{temps:?}");
+    }
+
+    #[test]
+    fn the_worse_conductor_slows_the_exchange_down() {
+        let mut rock = heat_row(CellType::ROCK, &[1000, AMBIENT_TEMP]);
+        let mut air = heat_row(CellType::ROCK, &[1000, AMBIENT_TEMP]);
+        let cell = Cell::build_cell(CellType::AIR, &mut air.rng);
+        air.set_cell(1, &cell);
+        rock.update_cell_temperature();
+        air.update_cell_temperature();
+
+        // air conducts heat worse than rock, so less of it gets across
+        assert!(rock.cells[1].temp > air.cells[1].temp, "rock: {}, air: {}", rock.cells[1].temp, air.cells[1].temp);
+        assert!(air.cells[1].temp > AMBIENT_TEMP);
+    }
+
+    #[test]
+    fn cells_change_phase_when_their_temperature_crosses_a_threshold() {
+        let mut space = Space::with_seed(7, 1, 0);
+        for (x, cell_type, temp) in [(0, CellType::WATER, 420), (2, CellType::WATER, 250), (4, CellType::LAVA, 1000), (6, CellType::SAND, 1300)] {
+            let cell = Cell { temp, ..Cell::build_cell(cell_type, &mut space.rng) };
+            space.set_cell(x, &cell);
+        }
+        space.update_cell_alchemy();
+
+        // boiling and melting take the latent heat away, freezing and solidifying give it back
+        let phases: Vec<(CellType, u16)> = [0, 2, 4, 6].iter().map(|&i| (space.cells[i].cell_type, space.cells[i].temp)).collect();
+        assert_eq!(phases, [(CellType::STEAM, 420 - 40), (CellType::ICE, 250 + 10), (CellType::ROCK, 1000 + 50), (CellType::GLASS, 1300 - 50)]);
+    }
+
+    #[test]
+    fn cells_between_their_thresholds_keep_their_phase() {
+        let mut space = Space::with_seed(3, 1, 0);
+        let water = Cell { temp: 373 + 39, ..Cell::build_cell(CellType::WATER, &mut space.rng) };
+        space.set_cell(0, &water);
+        let ice = Cell { temp: 273 + 9, ..Cell::build_cell(CellType::ICE, &mut space.rng) };
+        space.set_cell(2, &ice);
+        space.update_cell_alchemy();
+
+        // the latent heat has to be overcome as well before the phase changes
+        assert_eq!(space.cells[0].cell_type, CellType::WATER);
+        assert_eq!(space.cells[2].cell_type, CellType::ICE);
+    }
+
+    #[test]
+    fn fire_spreads_through_flammable_cells_and_leaves_its_products() {
+        for seed in 0..8 {
+            let mut space = Space::with_seed(12, 8, seed);
+            place(&mut space, CellType::ROCK, (0..12).map(|x| (x, 7)));
+            place(&mut space, CellType::WOOD, (1..12).flat_map(|x| (4..7).map(move |y| (x, y))));
+            place(&mut space, CellType::FIRE, (4..7).map(|y| (0, y)));
+            for _ in 0..1000 { tick(&mut space) }
+
+            // the whole block burnt down and shed its ash, then the fire went out. The rock below it does not burn
+            assert_eq!(count(&space, CellType::WOOD), 0, "seed: {seed}");
+            assert!(count(&space, CellType::ASH) > 0, "seed: {seed}");
+            assert_eq!(count(&space, CellType::FIRE), 0, "seed: {seed}");
+            assert_eq!(count(&space, CellType::ROCK), 12, "seed: {seed}");
+        }
+    }
+
+    #[test]
+    fn explosions_clear_their_radius() {
+        let mut space = Space::with_seed(41, 41, 0);
+        place(&mut space, CellType::SAND, (0..41).flat_map(|x| (0..41).map(move |y| (x, y))));
+        place(&mut space, CellType::GUNPOWDER, (19..22).flat_map(|x| (19..22).map(move |y| (x, y))));
+        space.explode(space.get_index(20, 20) as usize);
+
+        // nine cells of powder blow a radius of six. The sand close to the center can not withstand the blast, the sand outside of the radius is untouched
+        let radius = BASE_BLAST_RADIUS + 3.0 * BLAST_RADIUS_PER_CHARGE;
+        let hardness = CellTypeProperties::get_cell_properties(CellType::SAND).hardness;
+        for (x, y) in (0..41).flat_map(|x| (0..41).map(move |y| (x, y))) {
+            let distance = (((x - 20) * (x - 20) + (y - 20) * (y - 20)) as f32).sqrt();
+            let cell_type = space.cells[space.get_index(x, y) as usize].cell_type;
+            if distance < radius * (1.0 - hardness) { assert!(cell_type == CellType::AIR || cell_type == CellType::FIRE, "({x}, {y})") }
+            if distance > radius { assert_eq!(cell_type, CellType::SAND, "({x}, {y})") }
+        }
+        assert_eq!(count(&space, CellType::GUNPOWDER), 0);
+        assert!(!space.particles.particles.is_empty());
+    }
+
+    #[test]
+    fn charges_set_each_other_off_one_after_another() {
+        let mut space = Space::with_seed(32, 8, 0);
+        place(&mut space, CellType::ROCK, (0..32).map(|x| (x, 7)));
+        place(&mut space, CellType::GUNPOWDER, [(8, 6), (9, 6), (8, 5), (9, 5)]);
+        place(&mut space, CellType::GUNPOWDER, [(12, 6), (13, 6), (12, 5), (13, 5)]);
+
+        // the first charge goes off at the end of the pass, the second one is only primed by its heat
+        space.ignite(space.get_index(8, 6) as usize);
+        space.update_cell_alchemy();
+        assert_eq!(find(&space, CellType::GUNPOWDER), [(12, 5), (13, 5), (12, 6), (13, 6)]);
+
+        for _ in 0..20 { tick(&mut space) }
+        assert_eq!(count(&space, CellType::GUNPOWDER), 0);
+    }
+
+    #[test]
+    fn acid_eats_into_its_target_until_it_is_used_up() {
+        let mut space = Space::with_seed(1, 10, 0);
+        place(&mut space, CellType::ACID, [(0, 0)]);
+        place(&mut space, CellType::WOOD, (2..10).map(|y| (0, y)));
+        for _ in 0..500 { tick(&mut space) }
+
+        // every dissolved cell costs the acid a quarter of its strength, then it is diluted into water
+        assert_eq!(count(&space, CellType::ACID), 0);
+        assert_eq!(count(&space, CellType::WOOD), 8 - (ACID_STRENGTH / ACID_COST) as usize);
+        assert_eq!(count(&space, CellType::WATER), 1);
+    }
+
+    #[test]
+    fn acid_can_not_dissolve_resistant_materials() {
+        let mut space = Space::with_seed(3, 3, 0);
+        place(&mut space, CellType::GLASS, (0..3).flat_map(|x| (0..3).map(move |y| (x, y))));
+        place(&mut space, CellType::ACID, [(1, 1)]);
+        for _ in 0..500 { tick(&mut space) }
+
+        assert_eq!(count(&space, CellType::GLASS), 8);
+        assert_eq!(space.cells[4].cell_type, CellType::ACID);
+        assert_eq!(space.cells[4].strength, ACID_STRENGTH);
+    }
+
+    #[test]
+    fn reactions_turn_their_reactants_into_their_products() {
+        let mut space = Space::with_seed(2, 1, 0);
+        place(&mut space, CellType::WATER, [(0, 0)]);
+        place(&mut space, CellType::LAVA, [(1, 0)]);
+        for _ in 0..50 { space.update_cell_alchemy() }
+
+        assert_eq!(space.cells[0].cell_type, CellType::STEAM);
+        assert_eq!(space.cells[1].cell_type, CellType::ROCK);
+    }
+
+    #[test]
+    fn reactions_only_happen_within_their_temperature_range() {
+        let mut space = Space::with_seed(2, 1, 0);
+        let steam = Cell { temp: 600, ..Cell::build_cell(CellType::STEAM, &mut space.rng) };
+        space.set_cell(0, &steam);
+        place(&mut space, CellType::ICE, [(1, 0)]);
+        for _ in 0..100 { space.update_cell_alchemy() }
+
+        // the pair is too hot on average to condense the steam on the ice
+        assert_eq!(space.cells[0].cell_type, CellType::STEAM);
+        assert_eq!(space.cells[1].cell_type, CellType::ICE);
+    }
+
+    #[test]
+    fn circle_brushes_only_build_the_cells_they_paint() {
+        let mut circle = Space::with_seed(16, 16, 0);
+        circle.paint_bush((8, 8), 3, CellType::SAND, BrushType::Circle);
+
+        // painting the same cells one by one draws the same colours from the rng, so the corners of the square did not use it up
+        let mut single = Space::with_seed(16, 16, 0);
+        for (dx, dy) in (-3..=3).flat_map(|dx| (-3..=3).map(move |dy| (dx, dy))).filter(|(dx, dy)| dx * dx + dy * dy <= 9) {
+            single.paint_bush((8 + dx, 8 + dy), 0, CellType::SAND, BrushType::Square);
+        }
+        assert_eq!(count(&circle, CellType::SAND), 29);
+        assert!(circle.cells == single.cells);
+    }
+
+    /// returns whether the chunk that contains ```(x, y)``` has that cell marked as changed in the current tick
+    fn marked(space: &Space, (x, y): (i32, i32)) -> bool {
+        let chunk = space.chunks[space.get_chunk_index(space.get_index(x, y) as usize)];
+        chunk.next_dirty.is_some_and(|rect| (rect.min_x..=rect.max_x).contains(&x) && (rect.min_y..=rect.max_y).contains(&y))
+    }
+
+    #[test]
+    fn settled_piles_put_their_chunks_to_sleep() {
+        let mut space = Space::with_seed(128, 64, 0);
+        space.paint_bush((20, 10), 4, CellType::SAND, BrushType::Square);
+        assert!(!space.is_asleep(0));
+        assert!(space.is_asleep(100));
+
+        for _ in 0..300 { space.update_cell_behaviour() }
+        assert!(space.chunks.iter().all(|chunk| !chunk.is_awake()));
+        assert!(space.active_cells(true).is_empty());
+    }
+
+    #[test]
+    fn painting_and_changing_neighbours_wake_sleeping_chunks() {
+        let mut space = Space::with_seed(128, 64, 0);
+        place(&mut space, CellType::ROCK, (40..64).map(|y| (64, y)));
+        place(&mut space, CellType::SAND, (56..64).flat_map(|x| (40..64).map(move |y| (x, y))));
+        for _ in 0..300 { space.update_cell_behaviour() }
+        assert!(space.chunks.iter().all(|chunk| !chunk.is_awake()));
+
+        // painting into the right chunk only wakes that one
+        space.paint_bush((100, 63), 0, CellType::ROCK, BrushType::Square);
+        assert!(space.is_asleep(0));
+        assert!(!space.is_asleep(space.get_index(100, 63) as usize));
+        for _ in 0..5 { space.update_cell_behaviour() }
+        assert!(space.chunks.iter().all(|chunk| !chunk.is_awake()));
+
+        // digging out the wall wakes the sand leaning against it in the left chunk, which then flows over
+        place(&mut space, CellType::AIR, (40..64).map(|y| (64, y)));
+        assert!(!space.is_asleep(0));
+        for _ in 0..100 { space.update_cell_behaviour() }
+        assert!(find(&space, CellType::SAND).iter().any(|&(x, _)| x > 64));
+    }
+
+    #[test]
+    fn cells_crossing_a_chunk_border_mark_both_chunks() {
+        let mut space = Space::with_seed(64, 128, 0);
+        place(&mut space, CellType::SAND, [(10, 63)]);
+
+        // a cell that was just placed counts as updated, so it only falls in the tick after
+        for _ in 0..2 { space.update_cell_behaviour() }
+
+        assert_eq!(find(&space, CellType::SAND), [(10, 64)]);
+        assert!(marked(&space, (10, 63)));
+        assert!(marked(&space, (10, 64)));
+        assert!(space.chunks.iter().all(|chunk| chunk.is_awake()));
+    }
+
+    /// returns every cell of the grid and every particle that is not air, told apart by their temperature and sorted by it
+    fn census(space: &Space) -> Vec<(u16, CellType)> {
+        let particles = space.particles.particles.iter().map(|particle| &particle.cell);
+        let mut cells: Vec<(u16, CellType)> = space.cells.iter().chain(particles).filter(|cell| cell.cell_type != CellType::AIR).map(|cell| (cell.temp, cell.cell_type)).collect();
+        cells.sort_unstable_by_key(|&(temp, _)| temp);
+        cells
+    }
+
+    #[test]
+    fn moving_in_parallel_neither_loses_nor_duplicates_cells() {
+        for parallel in [false, true] {
+            let mut space = Space::with_seed(192, 192, 0);
+            space.parallel = parallel;
+
+            // every cell gets a temperature of its own to be recognised by, the movement never touches it
+            let materials = [CellType::SAND, CellType::WATER, CellType::OIL, CellType::GRAVEL, CellType::TOXIC_GAS, CellType::SMOKE];
+            let mut id = 1000;
+            for y in 20..120 {
+                for x in 10..182 {
+                    let cell = Cell { temp: id, ..Cell::build_cell(materials[((x / 12 + y / 9) % materials.len() as i32) as usize], &mut space.rng) };
+                    space.set_cell(space.get_index(x, y) as usize, &cell);
+                    id += 1;
+                }
+            }
+            let (start, grid) = (census(&space), space.cells.clone());
+
+            for tick in 0..150 {
+                space.update_cell_behaviour();
+                if tick % 10 == 0 { assert!(census(&space) == start, "cells were lost or duplicated in tick {tick} with parallel set to {parallel}") }
+            }
+            assert!(census(&space) == start, "cells were lost or duplicated with parallel set to {parallel}");
+            assert!(space.cells != grid, "nothing moved with parallel set to {parallel}");
+        }
+    }
+
+    #[test]
+    fn falling_cells_speed_up_and_stop_on_what_they_hit() {
+        let mut space = Space::with_seed(9, 64, 0);
+        place(&mut space, CellType::ROCK, (0..9).map(|x| (x, 60)));
+        place(&mut space, CellType::SAND, [(4, 0)]);
+
+        // a cell that fell at one cell per tick would only have made it down ten rows
+        for _ in 0..10 { space.update_cell_behaviour() }
+        let (_, y) = find(&space, CellType::SAND)[0];
+        assert!(y > 15, "{y}");
+
+        // it still lands right on top of the rock instead of skipping through it, and loses its speed
+        for _ in 0..50 { space.update_cell_behaviour() }
+        let sand = find(&space, CellType::SAND);
+        assert_eq!(sand.len(), 1);
+        assert_eq!(sand[0].1, 59);
+        assert_eq!(cell_at(&space, sand[0]).velocity.1, 0.0);
+    }
+
+    #[test]
+    fn hard_landings_spread_sideways() {
+        let drop = |height: i32| {
+            let mut space = Space::with_seed(64, 64, 0);
+            place(&mut space, CellType::SAND, (0..4).flat_map(|x| (0..4).map(move |y| (30 + x, 60 - height - y))));
+            for _ in 0..200 { space.update_cell_behaviour() }
+
+            // how far the sand ended up from where it fell
+            find(&space, CellType::SAND).iter().map(|&(x, _)| (x - 31).unsigned_abs()).sum::<u32>()
+        };
+        assert!(drop(56) > drop(0), "high: {}, low: {}", drop(56), drop(0));
+    }
+
+    #[test]
+    fn fast_cells_keep_sinking_through_liquids() {
+        let mut space = Space::with_seed(64, 64, 0);
+        place(&mut space, CellType::WATER, (0..64).flat_map(|x| (32..64).map(move |y| (x, y))));
+        place(&mut space, CellType::SAND, [(32, 0)]);
+
+        // losing the roll to sink through the water is no landing, so the grain neither spreads sideways nor loses its speed
+        for _ in 0..200 {
+            space.update_cell_behaviour();
+            let sand = find(&space, CellType::SAND)[0];
+            if sand.1 == 63 { return }
+            assert_eq!(cell_at(&space, sand).velocity.0, 0.0, "{sand:?}");
+            if sand.1 > 32 { assert!(cell_at(&space, sand).velocity.1 > 1.0, "{sand:?}") }
+        }
+        panic!("the grain never reached the bottom");
+    }
+
+    #[test]
+    fn liquids_slide_as_far_as_their_dispersion() {
+        for cell_type in [CellType::WATER, CellType::OIL, CellType::LAVA] {
+            let mut space = Space::with_seed(33, 1, 0);
+            place(&mut space, cell_type, [(16, 0)]);
+            let dispersion = CellTypeProperties::get_cell_properties(cell_type).dispersion;
+            assert!(space.try_disperse(16, dispersion, Gravity::DOWN));
+
+            let (x, _) = find(&space, cell_type)[0];
+            assert_eq!(x.abs_diff(16), dispersion as u32, "{cell_type:?}");
+        }
+    }
+
+    #[test]
+    fn viscous_liquids_ooze_instead_of_flowing() {
+        let spread = |cell_type: CellType| {
+            let mut space = Space::with_seed(64, 16, 0);
+            place(&mut space, cell_type, (30..34).flat_map(|x| (8..16).map(move |y| (x, y))));
+            for _ in 0..12 { space.update_cell_behaviour() }
+
+            // how wide the puddle got
+            find(&space, cell_type).iter().map(|&(x, _)| x).collect::<std::collections::HashSet<i32>>().len()
+        };
+        assert!(spread(CellType::WATER) > 2 * spread(CellType::LAVA), "water: {}, lava: {}", spread(CellType::WATER), spread(CellType::LAVA));
+    }
 }
@@ -0,0 +1,236 @@
+// imports:
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::cells_layer::Space;
+
+/// The ```charge``` of a conductor that was just hit by a spark. It counts down by one per tick and the conductor only takes a new spark once it is back at 0,
+/// so sparks run along a wire instead of back and forth
+pub const SPARK_LIFE: u8 = 4;
+
+/// The chance per tick and ```flammable``` neighbour that a fresh spark sets it on fire
+const SPARK_IGNITION: f32 = 0.05;
+
+/// # Functionality:
+/// The part that a material plays in a circuit
+/// # Options:
+/// ```Insulator``` does not take part. A ```Conductor``` passes sparks on to its neighbours and heats up by ```heating``` Kelvin for every spark, which is what makes resistive materials glow.
+/// ```PType``` and ```NType``` conduct as well, but a spark never passes from ```NType``` into ```PType```, so the two of them make a diode.
+/// A ```Source``` sparks every neighbouring conductor as soon as it can take a spark again.
+/// A ```Switch``` only conducts while it is ```on```. A spark from a ```PType``` neighbour switches it on and one from an ```NType``` neighbour switches it off, see ```toggles_into```.
+/// ```Not``` and ```And``` are logic gates. Every neighbouring source and every neighbouring conductor except ```NType``` is an input, which is on while it carries a spark.
+/// The output of a gate are its ```NType``` neighbours. ```Not``` sparks them once none of its inputs was on for ```SPARK_LIFE``` ticks and ```And``` sparks them while at least two of its inputs are on
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum Electric {
+    #[default]
+    Insulator,
+    Conductor { heating: u16 },
+    PType,
+    NType,
+    Source,
+    Switch { on: bool },
+    Not,
+    And,
+}
+
+impl Electric {
+
+    /// # Functionality:
+    /// Returns whether a cell of this kind passes sparks on
+    pub fn conducts(self) -> bool {
+        matches!(self, Electric::Conductor { .. } | Electric::PType | Electric::NType | Electric::Switch { on: true })
+    }
+}
+
+impl Space {
+
+    /// # Functionality:
+    /// Moves every spark one cell further and lets the sources, switches and logic gates act, see ```Electric```
+    /// # Behaviour:
+    /// A conductor with a ```charge``` of ```SPARK_LIFE``` carries a fresh spark, which jumps into every neighbouring conductor with a ```charge``` of 0, heats it up
+    /// and may set ```flammable``` neighbours on fire, see ```SPARK_IGNITION```. Everything that happens in a tick is decided from the state at its start and only applied afterwards,
+    /// so sparks travel exactly one cell per tick no matter in which order the cells are visited. Charged cells keep their chunk awake until they are discharged
+    pub fn update_electricity(&mut self) {
+        let cells = self.active_cells(true);
+        let mut sparks = Vec::new();
+        let mut ignited = Vec::new();
+        let mut held = Vec::new();
+
+        for &i in &cells {
+            let cell = self.cells[i];
+            let electric = cell.get_cell_properties().electric;
+            let neighbours = self.get_neighbours(i).into_iter().flatten();
+
+            match electric {
+                Electric::Source => sparks.extend(neighbours.map(|j| (electric, j))),
+                Electric::Not | Electric::And => {
+                    let inputs = neighbours.clone().filter(|&j| self.is_gate_input(j)).count();
+                    let fires = match electric {
+                        Electric::Not => inputs == 0 && cell.charge == 0,
+                        _ => inputs >= 2,
+                    };
+                    if electric == Electric::Not && inputs > 0 { held.push(i) }
+                    if fires {
+                        sparks.extend(neighbours.filter(|&j| self.cells[j].get_cell_properties().electric == Electric::NType).map(|j| (electric, j)));
+                    }
+                },
+                _ if electric.conducts() && cell.charge == SPARK_LIFE => {
+                    for j in neighbours {
+                        if self.cells[j].get_cell_properties().flammable && self.rng.gen::<f32>() < SPARK_IGNITION { ignited.push(j) }
+                        sparks.push((electric, j));
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        // the old sparks fade before the new ones are placed, so a spark never jumps back into the cell it came from
+        for &i in &cells {
+            if self.cells[i].charge == 0 { continue }
+            self.cells[i].charge -= 1;
+            self.mark_dirty(i);
+        }
+
+        // a not gate remembers its inputs for a while, so that it does not fire in the gap between two sparks
+        for i in held {
+            self.cells[i].charge = SPARK_LIFE;
+        }
+
+        for (from, j) in sparks {
+            let properties = self.cells[j].get_cell_properties();
+            match (from, properties.electric) {
+                (Electric::PType, Electric::Switch { on: false }) | (Electric::NType, Electric::Switch { on: true }) => {
+                    if let Some(into) = properties.toggles_into { self.convert_cell(j, into, self.cells[j].temp) }
+                },
+                (Electric::NType, Electric::PType) => {},
+                (_, to) if to.conducts() && self.cells[j].charge == 0 => {
+                    let heating = match to { Electric::Conductor { heating } => heating, _ => 0 };
+                    self.cells[j].charge = SPARK_LIFE;
+                    self.cells[j].temp = self.cells[j].temp.saturating_add(heating);
+                    self.mark_dirty(j);
+                },
+                _ => {},
+            }
+        }
+
+        // the spark may have already set it on fire through a neighbour
+        for j in ignited {
+            if self.cells[j].get_cell_properties().flammable { self.ignite(j) }
+        }
+    }
+
+    /// # Functionality:
+    /// Returns whether the cell at index j counts as an input of a neighbouring logic gate that is on, see ```Electric```
+    /// # Panic behaviour:
+    /// Panics if ```j > self.lenght```
+    fn is_gate_input(&self, j: usize) -> bool {
+        match self.cells[j].get_cell_properties().electric {
+            Electric::Source => true,
+            Electric::NType => false,
+            electric => electric.conducts() && self.cells[j].charge > 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cells_layer::CellType;
+    use crate::test_support::{material, place, tick};
+
+    /// returns the ```charge``` of the cell at ```(x, y)```
+    fn charge(space: &Space, (x, y): (i32, i32)) -> u8 { space.cells[space.get_index(x, y) as usize].charge }
+
+    /// builds a row of ```names``` from left to right at y = 1 and runs it for ```ticks``` with a spark placed into the left end
+    fn spark_row(names: &[&str], ticks: usize) -> Space {
+        let mut space = Space::with_seed(names.len() as i32, 3, 0);
+        for (x, name) in names.iter().enumerate() {
+            place(&mut space, material(name), [(x as i32, 1)]);
+        }
+        let i = space.get_index(0, 1) as usize;
+        space.cells[i].charge = SPARK_LIFE;
+        for _ in 0..ticks { tick(&mut space) }
+        space
+    }
+
+    #[test]
+    fn sparks_run_along_wires_one_cell_per_tick() {
+        for ticks in 1..12 {
+            let space = spark_row(&["Wire"; 16], ticks);
+
+            // the head of the spark, followed by its fading tail
+            assert_eq!(charge(&space, (ticks as i32, 1)), SPARK_LIFE);
+            for x in 0..16 {
+                let behind = ticks as i32 - x;
+                let expected = if (0..=SPARK_LIFE as i32).contains(&behind) { SPARK_LIFE - behind as u8 } else { 0 };
+                assert_eq!(charge(&space, (x, 1)), expected, "x: {x}, ticks: {ticks}");
+            }
+        }
+    }
+
+    #[test]
+    fn diodes_only_let_sparks_through_one_way() {
+        let forward = spark_row(&["Wire", "PSilicon", "NSilicon", "Wire", "Wire"], 4);
+        assert_eq!(charge(&forward, (4, 1)), SPARK_LIFE);
+
+        let backward = spark_row(&["Wire", "NSilicon", "PSilicon", "Wire", "Wire"], 4);
+        assert!((2..5).all(|x| charge(&backward, (x, 1)) == 0));
+    }
+
+    #[test]
+    fn switches_are_flipped_by_p_and_n_sparks() {
+        let off = spark_row(&["Wire", "Wire", "SwitchOff", "Wire"], 4);
+        assert_eq!(off.cells[off.get_index(2, 1) as usize].cell_type, material("SwitchOff"));
+        assert_eq!(charge(&off, (3, 1)), 0);
+
+        let on = spark_row(&["PSilicon", "SwitchOff", "Wire"], 2);
+        assert_eq!(on.cells[on.get_index(1, 1) as usize].cell_type, material("SwitchOn"));
+
+        let off_again = spark_row(&["NSilicon", "SwitchOn", "Wire"], 2);
+        assert_eq!(off_again.cells[off_again.get_index(1, 1) as usize].cell_type, material("SwitchOff"));
+    }
+
+    #[test]
+    fn batteries_heat_resistive_wire_until_it_lights_wood() {
+        let mut space = Space::with_seed(8, 3, 0);
+        place(&mut space, material("Battery"), [(0, 1)]);
+        place(&mut space, material("Nichrome"), (1..8).map(|x| (x, 1)));
+        place(&mut space, material("Wire"), (1..8).map(|x| (x, 2)));
+        place(&mut space, material("Wood"), (1..8).map(|x| (x, 0)));
+        for _ in 0..50 { tick(&mut space) }
+
+        // the copper wire barely warms up, apart from what it takes from the nichrome
+        let temp = |x, y| space.cells[space.get_index(x, y) as usize].temp;
+        assert!(temp(4, 1) > temp(4, 2) + 50, "nichrome: {}, wire: {}", temp(4, 1), temp(4, 2));
+        assert!(space.cells.iter().any(|cell| cell.cell_type == CellType::FIRE));
+    }
+
+    /// builds a gate of ```gate``` with an output of n-silicon to its right and a battery powered wire in every one of ```inputs```, the other cells around it are air
+    fn gate(gate: &str, inputs: &[(i32, i32)], ticks: usize) -> Vec<u8> {
+        let mut space = Space::with_seed(7, 7, 0);
+        place(&mut space, material(gate), [(3, 3)]);
+        place(&mut space, material("NSilicon"), [(4, 3), (5, 3)]);
+        for &(x, y) in inputs {
+            place(&mut space, material("Wire"), [(x, y)]);
+            place(&mut space, material("Battery"), [(x + x - 3, y + y - 3)]);
+        }
+
+        // the charge of the output, tick by tick
+        (0..ticks).map(|_| { tick(&mut space); charge(&space, (5, 3)) }).collect()
+    }
+
+    #[test]
+    fn and_gates_need_both_inputs() {
+        assert!(gate("AndGate", &[(3, 2), (3, 4)], 20).contains(&SPARK_LIFE));
+        assert!(gate("AndGate", &[(3, 2)], 20).iter().all(|&charge| charge == 0));
+        assert!(gate("AndGate", &[], 20).iter().all(|&charge| charge == 0));
+    }
+
+    #[test]
+    fn not_gates_invert_their_input() {
+        assert!(gate("NotGate", &[], 20).contains(&SPARK_LIFE));
+
+        // the gate only lets the output fade once it sees the input
+        assert!(gate("NotGate", &[(3, 2)], 30)[10..].iter().all(|&charge| charge == 0));
+    }
+}
@@ -0,0 +1,182 @@
+// imports:
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::cells_layer::{CellType, CellTypeProperties, Space, StateOfAggregation};
+use crate::texture_layer::shade;
+
+/// How much darker a source is drawn than the material it emits
+const SOURCE_SHADE: f32 = 0.6;
+
+/// # Functionality:
+/// Makes a material spawn fresh cells next to itself
+/// # Behaviour:
+/// Every tick a cell of the material places one cell into a random neighbouring air cell with the chance of ```rate``` (0.0 to 1.0), see ```Space::update_emitter()```.
+/// Each cell remembers what it spawns in its ```emits```, ```material``` is only what a freshly built cell starts with, see ```Space::set_emission()```
+/// # Structure:
+/// ```
+/// pub struct Emission {
+///     pub material: CellType,
+///     pub rate: f32,
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Emission {
+    pub material: CellType,
+    pub rate: f32,
+}
+
+/// # Functionality:
+/// Which of its neighbours a material destroys, see ```Space::update_drain()```
+/// # Options:
+/// ```Nothing``` leaves them alone. ```Flowing``` swallows granular cells, liquids and gases, like a drain does, and ```Everything``` eats every cell it touches, like the void does.
+/// Cells that emit or destroy matter themselves are never eaten, so sources, drains and the void can be built next to each other
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum Consumes {
+    #[default]
+    Nothing,
+    Flowing,
+    Everything,
+}
+
+impl Consumes {
+
+    /// # Functionality:
+    /// Returns whether a material that consumes this way destroys a cell of ```properties```
+    pub fn eats(self, properties: &CellTypeProperties) -> bool {
+        if properties.cell_type == CellType::AIR || properties.emits.is_some() || properties.consumes != Consumes::Nothing { return false }
+
+        match self {
+            Consumes::Nothing => false,
+            Consumes::Flowing => matches!(properties.state, StateOfAggregation::Granular | StateOfAggregation::Liquid | StateOfAggregation::Gas),
+            Consumes::Everything => true,
+        }
+    }
+}
+
+impl Space {
+
+    /// # Functionality:
+    /// Lets the cell at index i, which has an ```Emission```, spawn a fresh cell of the material it ```emits``` into a random neighbouring air cell
+    /// # Behaviour:
+    /// The new cell starts at the ```base_temp``` of its material. An emitter keeps its chunk awake as long as it has room to emit into, one that emits air does nothing
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn update_emitter(&mut self, i: usize) {
+        let Some(emission) = self.cells[i].get_cell_properties().emits else { return };
+        let material = self.cells[i].emits;
+        if material == CellType::AIR { return }
+
+        let free: Vec<usize> = self.get_neighbours(i).into_iter().flatten().filter(|j| self.cells[*j].cell_type == CellType::AIR).collect();
+        if free.is_empty() { return }
+
+        self.mark_dirty(i);
+        if self.rng.gen::<f32>() < emission.rate {
+            let j = free[self.rng.gen_range(0..free.len())];
+            self.convert_cell(j, material, CellTypeProperties::get_cell_properties(material).base_temp);
+        }
+    }
+
+    /// # Functionality:
+    /// Makes the source at index i emit ```material``` from now on and tints it in a darker shade of that material. Returns whether the cell took it
+    /// # Behaviour:
+    /// Only cells with an ```Emission``` take a material, and neither air nor another source can be emitted, so painting those over a source replaces it as usual
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn set_emission(&mut self, i: usize, material: CellType) -> bool {
+        let properties = CellTypeProperties::get_cell_properties(material);
+        if self.cells[i].get_cell_properties().emits.is_none() || material == CellType::AIR || properties.emits.is_some() { return false }
+
+        self.cells[i].emits = material;
+        self.cells[i].color = shade(properties.base_color, SOURCE_SHADE);
+        self.mark_dirty(i);
+        true
+    }
+
+    /// # Functionality:
+    /// Lets the cell at index i destroy every neighbour that it ```Consumes```, they are replaced by air
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn update_drain(&mut self, i: usize) {
+        let consumes = self.cells[i].get_cell_properties().consumes;
+
+        for j in self.get_neighbours(i).into_iter().flatten() {
+            if consumes.eats(self.cells[j].get_cell_properties()) { self.convert_cell(j, CellType::AIR, self.cells[j].temp) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cells_layer::{BrushType, CellType, Space};
+    use crate::test_support::{count, material, tick};
+
+    #[test]
+    fn sources_keep_producing() {
+        let mut space = Space::with_seed(32, 32, 0);
+        space.paint_bush((16, 2), 0, material("Source"), BrushType::Square);
+
+        let mut counts = Vec::new();
+        for _ in 0..4 {
+            for _ in 0..50 { tick(&mut space) }
+            counts.push(count(&space, CellType::WATER));
+        }
+
+        // the water flows away from the source, so it never runs out of room
+        assert!(counts.windows(2).all(|pair| pair[0] < pair[1]), "{counts:?}");
+        assert!(counts[3] > 50, "{counts:?}");
+    }
+
+    #[test]
+    fn drains_swallow_what_flows_into_them() {
+        let mut space = Space::with_seed(16, 24, 0);
+        space.paint_bush((8, 2), 0, material("Source"), BrushType::Square);
+        assert!(space.set_emission(space.get_index(8, 2) as usize, CellType::SAND));
+        for x in 0..16 { space.paint_bush((x, 23), 0, material("Drain"), BrushType::Square) }
+        space.paint_bush((3, 22), 0, CellType::ROCK, BrushType::Square);
+        for _ in 0..400 { tick(&mut space) }
+
+        // the sand never piles up, but the rock sitting on the drain is left alone
+        assert!(count(&space, CellType::SAND) < 30, "{}", count(&space, CellType::SAND));
+        assert_eq!(count(&space, material("Drain")), 16);
+        assert_eq!(space.cells[space.get_index(3, 22) as usize].cell_type, CellType::ROCK);
+    }
+
+    #[test]
+    fn the_void_eats_everything_it_touches() {
+        let mut space = Space::with_seed(9, 9, 0);
+        space.paint_bush((4, 4), 4, CellType::ROCK, BrushType::Square);
+        space.paint_bush((4, 4), 0, material("Void"), BrushType::Square);
+        space.paint_bush((4, 3), 0, material("Source"), BrushType::Square);
+        tick(&mut space);
+
+        // only the source survives next to it
+        let (x, y) = (4, 4);
+        for (nx, ny) in [(x + 1, y), (x, y + 1), (x - 1, y)] {
+            assert_eq!(space.cells[space.get_index(nx, ny) as usize].cell_type, CellType::AIR);
+        }
+        assert_eq!(space.cells[space.get_index(4, 3) as usize].cell_type, material("Source"));
+        assert_eq!(count(&space, CellType::ROCK), 81 - 5);
+    }
+
+    #[test]
+    fn each_source_emits_what_was_painted_onto_it() {
+        let mut space = Space::with_seed(48, 24, 0);
+        for x in [8, 24, 40] { space.paint_bush((x, 2), 0, material("Source"), BrushType::Square) }
+        let source = |space: &Space, x| space.get_index(x, 2) as usize;
+        assert!(space.set_emission(source(&space, 24), CellType::SAND));
+        assert!(space.set_emission(source(&space, 40), CellType::LAVA));
+
+        // air and other sources replace a source instead, and only sources take a material
+        assert!(!space.set_emission(source(&space, 8), CellType::AIR));
+        assert!(!space.set_emission(source(&space, 8), material("Source")));
+        assert!(!space.set_emission(space.get_index(8, 20) as usize, CellType::SAND));
+        for _ in 0..100 { tick(&mut space) }
+
+        assert_eq!(count(&space, material("Source")), 3);
+        assert_eq!(space.cells[source(&space, 8)].emits, CellType::WATER);
+        assert!(count(&space, CellType::WATER) > 0);
+        assert!(count(&space, CellType::SAND) > 0);
+        assert!(count(&space, CellType::LAVA) + count(&space, CellType::ROCK) > 0);
+    }
+}
@@ -0,0 +1,251 @@
+// imports:
+use crate::cells_layer::{DirtyRect, Space};
+
+/// The grid offsets of the directions of ```Gravity```, going around starting at straight down
+const DIRECTIONS: [(i32, i32); 8] = [(0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1), (1, 0), (1, 1)];
+
+/// # Functionality:
+/// One of the 8 directions of the grid that gravity can point in
+/// # Structure:
+/// ```
+/// pub struct Gravity(u8);
+/// ```
+/// The number is the position in a ring of all directions, so that ```rotate()``` can turn it in steps of 45 degrees
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Gravity(u8);
+
+#[allow(dead_code)]
+impl Gravity {
+    pub const DOWN: Gravity = Gravity(0);
+    pub const DOWN_LEFT: Gravity = Gravity(1);
+    pub const LEFT: Gravity = Gravity(2);
+    pub const UP_LEFT: Gravity = Gravity(3);
+    pub const UP: Gravity = Gravity(4);
+    pub const UP_RIGHT: Gravity = Gravity(5);
+    pub const RIGHT: Gravity = Gravity(6);
+    pub const DOWN_RIGHT: Gravity = Gravity(7);
+
+    /// # Functionality:
+    /// Returns the step in cells that this direction makes on the grid
+    pub fn offset(self) -> (i32, i32) { DIRECTIONS[self.0 as usize] }
+
+    /// # Functionality:
+    /// Returns this direction as a vector of length 1
+    pub fn unit(self) -> (f32, f32) {
+        let (x, y) = self.offset();
+        let length = ((x * x + y * y) as f32).sqrt();
+        (x as f32 / length, y as f32 / length)
+    }
+
+    /// # Functionality:
+    /// Turns the direction by ```steps``` times 45 degrees, positive steps go from down towards left
+    pub fn rotate(self, steps: i32) -> Gravity { Gravity((self.0 as i32 + steps).rem_euclid(8) as u8) }
+
+    /// # Functionality:
+    /// Returns the direction that points the other way
+    pub fn opposite(self) -> Gravity { self.rotate(4) }
+
+    /// # Functionality:
+    /// Returns whether this direction is one of the four diagonals
+    pub fn is_diagonal(self) -> bool { self.0 % 2 == 1 }
+
+    /// # Functionality:
+    /// Returns the direction that is closest to the vector ```(x, y)```, or ```None``` if it has no length
+    pub fn closest(x: f32, y: f32) -> Option<Gravity> {
+        if x == 0.0 && y == 0.0 { return None }
+        (0..8).map(Gravity).max_by(|a, b| {
+            let dot = |gravity: &Gravity| { let (ux, uy) = gravity.unit(); ux * x + uy * y };
+            dot(a).total_cmp(&dot(b))
+        })
+    }
+
+    /// # Functionality:
+    /// Turns a velocity that is given as ```(across, along)``` this direction into the coordinates of the grid. ```across``` is positive towards ```rotate(-2)```
+    pub fn to_grid(self, (across, along): (f32, f32)) -> (f32, f32) {
+        let (dx, dy) = self.unit();
+        let (sx, sy) = self.rotate(-2).unit();
+        (sx * across + dx * along, sy * across + dy * along)
+    }
+
+    /// # Functionality:
+    /// Returns the part of the grid velocity ```(x, y)``` that points along this direction
+    pub fn along(self, (x, y): (f32, f32)) -> f32 {
+        let (dx, dy) = self.unit();
+        dx * x + dy * y
+    }
+}
+
+/// # Functionality:
+/// This enum dictates where the gravity inside of a ```GravityField``` pulls
+/// # Options:
+/// ```Direction``` pulls everything the same way, for example sideways or up. ```Towards``` pulls everything into a single cell like a well
+/// and ```AwayFrom``` pushes everything out of it like an anti-gravity well
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GravityPull {
+    Direction(Gravity),
+    Towards((i32, i32)),
+    AwayFrom((i32, i32)),
+}
+
+/// # Functionality:
+/// A region of the space that overrides the gravity of the world
+/// # Structure:
+/// ```
+/// pub struct GravityField {
+///     pub area: DirtyRect,
+///     pub pull: GravityPull,
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GravityField {
+    pub area: DirtyRect,
+    pub pull: GravityPull,
+}
+
+impl GravityField {
+
+    /// # Functionality:
+    /// Returns the same field moved by ```(dx, dy)``` cells
+    pub fn translated(self, (dx, dy): (i32, i32)) -> GravityField {
+        let area = DirtyRect { min_x: self.area.min_x + dx, min_y: self.area.min_y + dy, max_x: self.area.max_x + dx, max_y: self.area.max_y + dy };
+        let pull = match self.pull {
+            GravityPull::Direction(gravity) => GravityPull::Direction(gravity),
+            GravityPull::Towards((x, y)) => GravityPull::Towards((x + dx, y + dy)),
+            GravityPull::AwayFrom((x, y)) => GravityPull::AwayFrom((x + dx, y + dy)),
+        };
+        GravityField { area, pull }
+    }
+
+    /// # Functionality:
+    /// Returns the gravity of this field at ```(x, y)```, or ```None``` if the cell is outside of its ```area``` or right in the centre of a well
+    pub fn gravity_at(&self, (x, y): (i32, i32)) -> Option<Gravity> {
+        if x < self.area.min_x || x > self.area.max_x || y < self.area.min_y || y > self.area.max_y { return None }
+        match self.pull {
+            GravityPull::Direction(gravity) => Some(gravity),
+            GravityPull::Towards((cx, cy)) => Gravity::closest((cx - x) as f32, (cy - y) as f32),
+            GravityPull::AwayFrom((cx, cy)) => Gravity::closest((x - cx) as f32, (y - cy) as f32),
+        }
+    }
+}
+
+/// # Functionality:
+/// Holds the gravity of a ```Space```
+/// # Structure:
+/// ```
+/// pub struct GravityLayer {
+///     pub direction: Gravity,
+///     pub fields: Vec<GravityField>,
+/// }
+/// ```
+/// ```direction``` is the gravity of the whole world. Where ```fields``` overlap the one that comes last wins
+#[derive(Clone, Debug)]
+pub struct GravityLayer {
+    pub direction: Gravity,
+    pub fields: Vec<GravityField>,
+}
+
+impl Default for GravityLayer {
+    fn default() -> Self { GravityLayer { direction: Gravity::DOWN, fields: Vec::new() } }
+}
+
+impl GravityLayer {
+
+    /// # Functionality:
+    /// Returns the same gravity with every field moved by ```(dx, dy)``` cells
+    pub fn translated(&self, offset: (i32, i32)) -> GravityLayer {
+        GravityLayer { direction: self.direction, fields: self.fields.iter().map(|field| field.translated(offset)).collect() }
+    }
+}
+
+impl Space {
+
+    /// # Functionality:
+    /// Returns the direction that gravity pulls the cell at index i in
+    /// # Behaviour:
+    /// The last ```GravityField``` that covers the cell decides, otherwise the gravity of the world does
+    pub fn gravity_at(&self, i: usize) -> Gravity {
+        let coordinates = self.get_coordinates(i as isize);
+        self.gravity.fields.iter().rev().find_map(|field| field.gravity_at(coordinates)).unwrap_or(self.gravity.direction)
+    }
+
+    /// # Functionality:
+    /// Returns the index of the cell next to i in ```direction```, or ```None``` if that is outside of the space
+    pub fn get_neighbour(&self, i: isize, direction: Gravity) -> Option<isize> {
+        let (x, y) = self.get_coordinates(i);
+        let (dx, dy) = direction.offset();
+        self.get_index_checked(x + dx, y + dy).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cells_layer::CellType;
+    use crate::test_support::{find, place};
+
+    #[test]
+    fn directions_turn_around_the_grid() {
+        assert_eq!(Gravity::DOWN.opposite(), Gravity::UP);
+        assert_eq!(Gravity::DOWN.rotate(2), Gravity::LEFT);
+        assert_eq!(Gravity::DOWN.rotate(-2), Gravity::RIGHT);
+        assert_eq!(Gravity::closest(3.0, 2.5), Some(Gravity::DOWN_RIGHT));
+        assert_eq!(Gravity::closest(-4.0, 1.0), Some(Gravity::LEFT));
+        assert_eq!(Gravity::closest(0.0, 0.0), None);
+    }
+
+    #[test]
+    fn sand_falls_sideways() {
+        let mut space = Space::with_seed(16, 4, 0);
+        space.gravity.direction = Gravity::RIGHT;
+        place(&mut space, CellType::SAND, (0..4).map(|y| (0, y)));
+        for _ in 0..100 { space.update_cell_behaviour() }
+
+        assert!(find(&space, CellType::SAND).iter().all(|&(x, _)| x == 15));
+    }
+
+    #[test]
+    fn diagonal_gravity_fills_the_corner() {
+        let mut space = Space::with_seed(16, 16, 0);
+        space.gravity.direction = Gravity::DOWN_RIGHT;
+        place(&mut space, CellType::SAND, [(2, 8)]);
+        for _ in 0..100 { space.update_cell_behaviour() }
+
+        assert_eq!(find(&space, CellType::SAND), [(15, 15)]);
+    }
+
+    #[test]
+    fn gases_rise_against_the_local_gravity() {
+        let mut space = Space::with_seed(16, 16, 0);
+        space.gravity.direction = Gravity::LEFT;
+        place(&mut space, CellType::TOXIC_GAS, (0..16).map(|y| (0, y)));
+        for _ in 0..200 { space.update_cell_behaviour() }
+
+        let gas = find(&space, CellType::TOXIC_GAS);
+        assert_eq!(gas.len(), 16);
+        assert!(gas.iter().map(|&(x, _)| x).sum::<i32>() > 8 * 16);
+    }
+
+    #[test]
+    fn fields_override_the_gravity_of_the_world() {
+        let mut space = Space::with_seed(8, 16, 0);
+        let area = DirtyRect { min_x: 0, min_y: 0, max_x: 7, max_y: 15 };
+        space.gravity.fields.push(GravityField { area, pull: GravityPull::Direction(Gravity::UP) });
+        place(&mut space, CellType::SAND, (0..8).map(|x| (x, 15)));
+        for _ in 0..100 { space.update_cell_behaviour() }
+
+        assert!(find(&space, CellType::SAND).iter().all(|&(_, y)| y == 0));
+    }
+
+    #[test]
+    fn wells_pull_everything_into_their_centre() {
+        let mut space = Space::with_seed(33, 33, 0);
+        let area = DirtyRect { min_x: 0, min_y: 0, max_x: 32, max_y: 32 };
+        space.gravity.fields.push(GravityField { area, pull: GravityPull::Towards((16, 16)) });
+        place(&mut space, CellType::SAND, (0..33).step_by(4).flat_map(|x| [(x, 0), (x, 32), (0, x), (32, x)]));
+        for _ in 0..300 { space.update_cell_behaviour() }
+
+        let sand = find(&space, CellType::SAND);
+        assert!(sand.iter().all(|&(x, y)| (x - 16).abs() <= 5 && (y - 16).abs() <= 5), "{sand:?}");
+    }
+}
@@ -1,8 +1,21 @@
 mod cells_layer;
+mod electricity_layer;
+mod emitter_layer;
+mod gravity_layer;
+mod material_registry;
+mod moisture_layer;
+mod particle_layer;
 mod player_layer;
-use crate::cells_layer::Space;
+mod rigid_body_layer;
+mod structure_layer;
+#[cfg(test)]
+mod test_support;
+mod texture_layer;
+mod world_layer;
+use crate::material_registry::MaterialRegistry;
+use crate::world_layer::World;
 
-use log::error;
+use log::{error, warn};
 use pixels::{Error, Pixels, SurfaceTexture};
 
 use winit::dpi::LogicalSize;
@@ -28,6 +41,15 @@ const SCALE: f32 = 5.0;
 // here are the env variables that toggle dev tools
 const TOGGLE_DESCRIPTOR:bool = true;
 
+// the material definitions that are loaded at startup
+const MATERIALS_PATH: &str = "materials.ron";
+
+// where the chunks of the world are stored when they are out of range
+const SAVE_DIRECTORY: &str = "world";
+
+// how many cells the player moves per frame
+const PLAYER_SPEED: i64 = 2;
+
 fn main() -> Result<(), Error> {
     let mut step_by_frame = false;
     // debug section
@@ -37,6 +59,17 @@ fn main() -> Result<(), Error> {
 
     // builds the Widow
     env_logger::init();
+
+    // load the materials before anything touches a cell, fall back to the built-in ones if there is no file
+    match MaterialRegistry::load_file(MATERIALS_PATH) {
+        Ok(registry) => { registry.install(); },
+        Err(material_registry::MaterialError::Io(path, err)) if err.kind() == std::io::ErrorKind::NotFound => warn!("{path} not found, using the built-in materials"),
+        Err(err) => {
+            error!("{err}");
+            std::process::exit(1);
+        },
+    }
+
     let event_loop = EventLoop::new();
     let mut input = WinitInputHelper::new();
     let window = {
@@ -59,14 +92,20 @@ fn main() -> Result<(), Error> {
     };
 
     // this is where the magic starts
-    let mut simulation_space = cells_layer::Space::new(WIDTH, HEIGHT);
     let mut player = player_layer::PlayerState::new((0, 0),100);
+    let mut world = match World::new(player.positon, SAVE_DIRECTORY, rand::random()) {
+        Ok(world) => world,
+        Err(err) => {
+            error!("could not load the world from {SAVE_DIRECTORY}: {err}");
+            std::process::exit(1);
+        },
+    };
     let mut counter:usize = 0;
     event_loop.run(move |event, _, control_flow| {
         
         // Draw the current frame
         if let Event::RedrawRequested(_) = event {
-            simulation_space.draw(pixels.get_frame_mut(), player);
+            world.draw(pixels.get_frame_mut(), player);
             if let Err(err) = pixels.render() {
                 error!("pixels.render() failed: {err}");
                 *control_flow = ControlFlow::Exit;
@@ -79,6 +118,9 @@ fn main() -> Result<(), Error> {
 
             // Exit events
             if input.key_pressed(VirtualKeyCode::Escape) || input.quit() {
+                if let Err(err) = world.save_all() {
+                    error!("could not save the world: {err}");
+                }
                 *control_flow = ControlFlow::Exit;
                 return;
             }
@@ -93,66 +135,80 @@ fn main() -> Result<(), Error> {
 
 
             {
-                let mouse_pos = match input.mouse() {
-                    Some(mouse_position_raw) => ((mouse_position_raw.0 / SCALE).trunc() as i32, (mouse_position_raw.1 / SCALE).trunc() as i32) ,
+                let screen_pos = match input.mouse() {
+                    Some(mouse_position_raw) => ((mouse_position_raw.0 / SCALE).trunc() as i64, (mouse_position_raw.1 / SCALE).trunc() as i64) ,
                     None => (0, 0),
                 };
 
-                #[allow(unused_parens)]
-                match simulation_space.get_index_checked(mouse_pos.0, mouse_pos.1) {
-                    Ok(i) => {
-                        if input.mouse_held(1) { 
-                            simulation_space.paint_bush(mouse_pos, 5, cells_layer::CellType::Sand, cells_layer::BrushType::Circle);
-                        }
-                        
+                // the screen is centered on the player
+                let camera = camera_origin(player);
+                // a mouse outside of the loaded chunks does not touch anything
+                let mouse_pos = world.to_local((camera.0 + screen_pos.0, camera.1 + screen_pos.1)).unwrap_or((-1, -1));
+                let simulation_space = &mut world.space;
 
-                        if input.mouse_held(2) { 
-                            let a = cells_layer::CellTypeProperties::rand_cell_properties();
-                            simulation_space.set_cell_checked(i, &cells_layer::Cell { 
-                                cell_type: a.cell_type, 
-                                color: a.base_color, 
-                                generation: 0, 
-                                temp: 298 
-                            }).ok();
-                        }
-                        if input.key_pressed(VirtualKeyCode::Return) {
-                            step_by_frame = !step_by_frame;
-                        }
-                        if input.key_pressed(VirtualKeyCode::Space) {
-                            simulation_space.update_cell_behaviour();
-                            simulation_space.update_cell_alchemy();
-                        }
-                        
-                        if input.key_pressed(VirtualKeyCode::P) { counter += 1 }
-                        if TOGGLE_DESCRIPTOR && simulation_space.index_inbounds(i) {
-                            let a = simulation_space.cells[i as usize].get_cell_properties().name;
-                            
-                            
-                            let b = cells_layer::CellTypeProperties::get_cell_by_number(&counter);
-                            if input.mouse_held(0) { 
-                                simulation_space.set_cell_checked(i, &cells_layer::Cell { 
-                                    cell_type: b.0, 
-                                    color: cells_layer::CellTypeProperties::get_cell_properties(b.0).base_color, 
-                                    generation: 0, 
-                                    temp: 298
-                                    }
-                                ).ok();
-                            }
-                            
-                            //print!("The selected Material is {} | You are looking at {}                                          \r",b.1, a);
+                if let Ok(i) = simulation_space.get_index_checked(mouse_pos.0, mouse_pos.1) {
+                    if input.mouse_held(1) { 
+                        simulation_space.paint_bush(mouse_pos, 5, cells_layer::CellType::SAND, cells_layer::BrushType::Circle);
+                    }
+                    
+
+                    if input.mouse_held(2) { 
+                        let a = cells_layer::CellTypeProperties::rand_cell_properties();
+                        let cell = simulation_space.paint_cell(i as usize, a.cell_type);
+                        simulation_space.set_cell_checked(i, &cell).ok();
+                    }
+                    if input.key_pressed(VirtualKeyCode::Return) {
+                        step_by_frame = !step_by_frame;
+                    }
+                    if input.key_pressed(VirtualKeyCode::Space) {
+                        simulation_space.update_cell_behaviour();
+                        simulation_space.update_cell_temperature();
+                        simulation_space.update_cell_alchemy();
+                    }
+                    
+                    if input.key_pressed(VirtualKeyCode::P) { counter += 1 }
+
+                    if TOGGLE_DESCRIPTOR && simulation_space.index_inbounds(i) {
+                        let b = cells_layer::CellTypeProperties::get_cell_by_number(&counter);
+                        // painting onto a source makes it emit the selected material instead of covering it
+                        if input.mouse_held(0) && !simulation_space.set_emission(i as usize, b.0) { 
+                            let cell = simulation_space.paint_cell(i as usize, b.0);
+                            simulation_space.set_cell_checked(i, &cell).ok();
                         }
                         
-                    },
-                    Err(_) => (),
+                        //print!("The selected Material is {}                                          \r",b.1);
+                    }
                 }
+
+                // spreads the simulation over all cores, no matter where the mouse is
+                if input.key_pressed(VirtualKeyCode::M) { simulation_space.parallel = !simulation_space.parallel }
+
+                // cycles through the orders in which the cells are moved
+                if input.key_pressed(VirtualKeyCode::O) { simulation_space.sweep = simulation_space.sweep.next() }
+
+                // turns the gravity of the world by 45 degrees
+                if input.key_pressed(VirtualKeyCode::G) { simulation_space.gravity.direction = simulation_space.gravity.direction.rotate(1) }
             }
 
 
             // player.player_movement(&simulation_space, &input);
+            if input.key_held(VirtualKeyCode::Left) { player.positon.0 -= PLAYER_SPEED }
+            if input.key_held(VirtualKeyCode::Right) { player.positon.0 += PLAYER_SPEED }
+            if input.key_held(VirtualKeyCode::Up) { player.positon.1 -= PLAYER_SPEED }
+            if input.key_held(VirtualKeyCode::Down) { player.positon.1 += PLAYER_SPEED }
+
+            // stream the chunks around the player
+            if let Err(err) = world.update_anchor(player.positon) {
+                error!("could not stream the world: {err}");
+                *control_flow = ControlFlow::Exit;
+                return;
+            }
+
             pixels.get_frame_mut();
             if !step_by_frame {
-                simulation_space.update_cell_behaviour();
-                simulation_space.update_cell_alchemy();
+                world.space.update_cell_behaviour();
+                world.space.update_cell_temperature();
+                world.space.update_cell_alchemy();
             }
             window.request_redraw();
 
@@ -162,17 +218,39 @@ fn main() -> Result<(), Error> {
     
 }
 
-impl Space {
+/// # Functionality:
+/// Returns the world coordinates of the top left corner of the screen, which is centered on the player
+fn camera_origin(player: player_layer::PlayerState) -> (i64, i64) {
+    (player.positon.0 - WIDTH as i64 / 2, player.positon.1 - HEIGHT as i64 / 2)
+}
+
+impl World {
     fn draw(&self, frame: &mut [u8], player: player_layer::PlayerState) {
+        let camera = camera_origin(player);
         for (i, pixel) in frame.chunks_exact_mut(4).enumerate() {
+            let position = (camera.0 + (i as i32 % WIDTH) as i64, camera.1 + (i as i32 / WIDTH) as i64);
 
-            let mut rgba = self.cells[i].color;
-            if self.get_coordinates(i as isize) == player.positon {
-                rgba = [0xFF, 0x0, 0x0, 0xFF];
-            }
-
+            // whatever is not loaded stays black
+            let rgba = match self.to_local(position) {
+                Some((x, y)) => self.space.cells[self.space.get_index(x, y) as usize].display_color(),
+                None => [0x0, 0x0, 0x0, 0xFF],
+            };
 
             pixel.copy_from_slice(&rgba);
         }
+
+        // the particles and bodies fly in front of the grid and the player in front of everything
+        let particles = self.space.particles.particles.iter().map(|particle| (particle.position, particle.cell.display_color()));
+        let bodies = self.space.bodies.bodies.iter().flat_map(|body| {
+            body.cell_positions(body.position, body.angle).zip(body.cells.iter().map(|(_, cell)| cell.display_color()))
+        });
+        let flying = particles.chain(bodies).map(|(position, rgba)| (self.to_world((position.0.round() as i32, position.1.round() as i32)), rgba));
+        for (position, rgba) in flying.chain([(player.positon, [0xFF, 0x0, 0x0, 0xFF])]) {
+            let (x, y) = (position.0 - camera.0, position.1 - camera.1);
+            if x < 0 || y < 0 || x >= WIDTH as i64 || y >= HEIGHT as i64 { continue }
+
+            let i = (x + y * WIDTH as i64) as usize * 4;
+            frame[i..i + 4].copy_from_slice(&rgba);
+        }
     }
 }
@@ -0,0 +1,453 @@
+// imports:
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::cells_layer::{CellType, CellTypeProperties, PhaseTransition, Reaction, StateOfAggregation, AMBIENT_TEMP, UPDATE_REACH};
+use crate::electricity_layer::Electric;
+use crate::emitter_layer::{Consumes, Emission};
+use crate::structure_layer::STRUCTURE_REACH;
+use crate::texture_layer::Texture;
+
+/// The material definitions that are compiled into the binary. They are used whenever no other registry was installed
+const DEFAULT_MATERIALS: &str = include_str!("../materials.ron");
+
+/// # Functionality:
+/// The names of the materials that the engine itself relies on, for example to spawn fire or smoke
+/// # Behaviour:
+/// The position of a name in this array is its fixed ```CellType``` id, no matter where it is defined in the file. They have to match the constants on ```CellType```
+pub const BUILTIN_MATERIALS: [&str; 17] = [
+    "Air", "Rock", "Water", "Sand", "Gravel", "Wood", "Steam", "Gunpowder", "Oil",
+    "Lava", "Acid", "Glass", "Ice", "Fire", "Smoke", "Ash", "ToxicGas",
+];
+
+/// The registry that all lookups go through, see ```MaterialRegistry::get()```
+static REGISTRY: OnceLock<MaterialRegistry> = OnceLock::new();
+
+/// # Functionality:
+/// All the reasons why a material file can be rejected. The ```Display``` implementation is meant to be shown to whoever wrote the file
+#[derive(Debug)]
+pub enum MaterialError {
+    Io(String, std::io::Error),
+    Parse(ron::error::SpannedError),
+    DuplicateMaterial(String),
+    MissingMaterial(&'static str),
+    UnknownMaterial { referenced_by: String, name: String },
+    InvalidValue { material: String, field: &'static str, reason: &'static str },
+    DuplicateReaction(String, String),
+    TooManyMaterials(usize),
+}
+
+impl fmt::Display for MaterialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaterialError::Io(path, err) => write!(f, "could not read the material file '{path}': {err}"),
+            MaterialError::Parse(err) => write!(f, "the material file is malformed at {err}"),
+            MaterialError::DuplicateMaterial(name) => write!(f, "the material '{name}' is defined more than once"),
+            MaterialError::MissingMaterial(name) => write!(f, "the material '{name}' is required by the engine but is not defined"),
+            MaterialError::UnknownMaterial { referenced_by, name } => write!(f, "'{referenced_by}' references the material '{name}', which is not defined"),
+            MaterialError::InvalidValue { material, field, reason } => write!(f, "the field '{field}' of '{material}' {reason}"),
+            MaterialError::DuplicateReaction(a, b) => write!(f, "the reaction between '{a}' and '{b}' is defined more than once"),
+            MaterialError::TooManyMaterials(count) => write!(f, "{count} materials are defined, but at most {} are supported", u16::MAX),
+        }
+    }
+}
+
+impl std::error::Error for MaterialError {}
+
+/// # Functionality:
+/// The layout of a material file as it is written by hand
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaterialFile {
+    materials: Vec<MaterialDefinition>,
+    #[serde(default)]
+    reactions: Vec<ReactionDefinition>,
+}
+
+/// # Functionality:
+/// A single material as it is written by hand. Materials are referenced by name, which ```MaterialRegistry::from_ron()``` resolves into ```CellType``` ids
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaterialDefinition {
+    name: String,
+    state: StateOfAggregation,
+    density: f32,
+    #[serde(default)]
+    hardness: f32,
+    #[serde(default)]
+    corrosion_resistance: f32,
+    #[serde(default = "default_temp_coefficient")]
+    temp_coefficient: f32,
+    #[serde(default)]
+    flammable: bool,
+    #[serde(default)]
+    explosive: bool,
+    #[serde(default)]
+    burn_rate: f32,
+    #[serde(default = "default_burn_product")]
+    burn_product: String,
+    #[serde(default = "default_base_temp")]
+    base_temp: u16,
+    #[serde(default)]
+    upper_phase: Option<PhaseDefinition>,
+    #[serde(default)]
+    lower_phase: Option<PhaseDefinition>,
+    #[serde(default = "default_dispersion")]
+    dispersion: u8,
+    #[serde(default)]
+    viscosity: f32,
+    #[serde(default)]
+    lifetime: Option<(u16, u16)>,
+    #[serde(default = "default_burn_product")]
+    decays_into: String,
+    #[serde(default)]
+    support_strength: Option<u16>,
+    #[serde(default = "default_burn_product")]
+    crumbles_into: String,
+    #[serde(default)]
+    moisture_capacity: u8,
+    #[serde(default)]
+    soaked_into: Option<String>,
+    #[serde(default)]
+    electric: Electric,
+    #[serde(default)]
+    toggles_into: Option<String>,
+    #[serde(default)]
+    emits: Option<EmissionDefinition>,
+    #[serde(default)]
+    consumes: Consumes,
+    #[serde(default)]
+    color_variation: f32,
+    #[serde(default)]
+    texture: Texture,
+    base_color: [u8; 4],
+}
+
+/// # Functionality:
+/// The hand written form of a ```PhaseTransition```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PhaseDefinition {
+    temp: u16,
+    into: String,
+    latent_heat: u16,
+}
+
+/// # Functionality:
+/// The hand written form of an ```Emission```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EmissionDefinition {
+    material: String,
+    rate: f32,
+}
+
+/// # Functionality:
+/// The hand written form of a ```Reaction```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReactionDefinition {
+    reactants: (String, String),
+    products: (String, String),
+    probability: f32,
+    #[serde(default = "default_temp_range")]
+    temp_range: (u16, u16),
+    #[serde(default)]
+    heat: i16,
+}
+
+fn default_temp_coefficient() -> f32 { 0.1 }
+fn default_burn_product() -> String { BUILTIN_MATERIALS[0].to_string() }
+fn default_base_temp() -> u16 { AMBIENT_TEMP }
+fn default_dispersion() -> u8 { 1 }
+fn default_temp_range() -> (u16, u16) { (0, u16::MAX) }
+
+/// # Functionality:
+/// Holds every material and reaction the simulation knows about. Materials are addressed by their ```CellType``` id, which is the index into ```materials```
+/// # Structure:
+/// ```
+/// pub struct MaterialRegistry {
+///     materials: Vec<CellTypeProperties>,
+///     reactions: Vec<Reaction>,
+///     reaction_lookup: HashMap<(CellType, CellType), (usize, bool)>,
+/// }
+/// ```
+pub struct MaterialRegistry {
+    materials: Vec<CellTypeProperties>,
+    reactions: Vec<Reaction>,
+    reaction_lookup: HashMap<(CellType, CellType), (usize, bool)>,
+}
+
+impl MaterialRegistry {
+
+    /// # Functionality:
+    /// Returns the installed registry. If none was installed the registry compiled from ```materials.ron``` is installed and returned
+    /// # Panic behaviour:
+    /// Panics if the compiled in material file is invalid, which is a bug
+    pub fn get() -> &'static MaterialRegistry {
+        REGISTRY.get_or_init(|| MaterialRegistry::from_ron(DEFAULT_MATERIALS).unwrap_or_else(|err| panic!("the built-in materials are invalid: {err}")))
+    }
+
+    /// # Functionality:
+    /// Makes this registry the one that ```get()``` returns. Returns ```false``` if a registry was already in use, in which case nothing changes
+    pub fn install(self) -> bool { REGISTRY.set(self).is_ok() }
+
+    /// # Functionality:
+    /// Reads and validates the material file at ```path```
+    pub fn load_file(path: impl AsRef<Path>) -> Result<MaterialRegistry, MaterialError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|err| MaterialError::Io(path.display().to_string(), err))?;
+        MaterialRegistry::from_ron(&source)
+    }
+
+    /// # Functionality:
+    /// Parses and validates a material file written in RON
+    /// # Behaviour:
+    /// The ```BUILTIN_MATERIALS``` get their fixed ids, every other material gets the next free id in the order of the file.
+    /// Names are leaked into ```&'static str``` so that ```CellTypeProperties``` stays ```Copy```, which is fine since a registry lives as long as the program
+    pub fn from_ron(source: &str) -> Result<MaterialRegistry, MaterialError> {
+        let file: MaterialFile = ron::from_str(source).map_err(MaterialError::Parse)?;
+
+        if file.materials.len() > u16::MAX as usize { return Err(MaterialError::TooManyMaterials(file.materials.len())) }
+
+        // hand out the ids first, so that materials can reference ones that are defined further down
+        let mut ids: HashMap<&str, CellType> = HashMap::new();
+        let mut next_id = BUILTIN_MATERIALS.len() as u16;
+        for definition in &file.materials {
+            let id = match BUILTIN_MATERIALS.iter().position(|name| *name == definition.name) {
+                Some(position) => CellType(position as u16),
+                None => { next_id += 1; CellType(next_id - 1) },
+            };
+            if ids.insert(&definition.name, id).is_some() { return Err(MaterialError::DuplicateMaterial(definition.name.clone())) }
+        }
+        if let Some(missing) = BUILTIN_MATERIALS.iter().find(|name| !ids.contains_key(*name)) { return Err(MaterialError::MissingMaterial(missing)) }
+
+        let resolve = |referenced_by: &str, name: &str| ids.get(name).copied().ok_or_else(|| MaterialError::UnknownMaterial { referenced_by: referenced_by.to_string(), name: name.to_string() });
+
+        let mut materials = vec![None; file.materials.len()];
+        for definition in &file.materials {
+            validate_material(definition)?;
+
+            let phase = |phase: &Option<PhaseDefinition>| -> Result<Option<PhaseTransition>, MaterialError> {
+                match phase {
+                    Some(phase) => Ok(PhaseTransition::at(phase.temp, resolve(&definition.name, &phase.into)?, phase.latent_heat)),
+                    None => Ok(None),
+                }
+            };
+
+            let id = ids[definition.name.as_str()];
+            materials[id.0 as usize] = Some(CellTypeProperties {
+                name: Box::leak(definition.name.clone().into_boxed_str()),
+                cell_type: id,
+                state: definition.state,
+                density: definition.density,
+                hardness: definition.hardness,
+                corrosion_resistance: definition.corrosion_resistance,
+                temp_coefficient: definition.temp_coefficient,
+                flammable: definition.flammable,
+                explosive: definition.explosive,
+                burn_rate: definition.burn_rate,
+                burn_product: resolve(&definition.name, &definition.burn_product)?,
+                base_temp: definition.base_temp,
+                upper_phase: phase(&definition.upper_phase)?,
+                lower_phase: phase(&definition.lower_phase)?,
+                dispersion: definition.dispersion,
+                viscosity: definition.viscosity,
+                lifetime: definition.lifetime,
+                decays_into: resolve(&definition.name, &definition.decays_into)?,
+                support_strength: definition.support_strength,
+                crumbles_into: resolve(&definition.name, &definition.crumbles_into)?,
+                moisture_capacity: definition.moisture_capacity,
+                soaked_into: definition.soaked_into.as_ref().map(|name| resolve(&definition.name, name)).transpose()?,
+                electric: definition.electric,
+                toggles_into: definition.toggles_into.as_ref().map(|name| resolve(&definition.name, name)).transpose()?,
+                emits: match &definition.emits {
+                    Some(emission) => Some(Emission { material: resolve(&definition.name, &emission.material)?, rate: emission.rate }),
+                    None => None,
+                },
+                consumes: definition.consumes,
+                color_variation: definition.color_variation,
+                texture: definition.texture,
+                base_color: definition.base_color,
+            });
+        }
+
+        let mut reactions = Vec::with_capacity(file.reactions.len());
+        let mut reaction_lookup = HashMap::new();
+        for definition in &file.reactions {
+            let label = format!("the reaction between '{}' and '{}'", definition.reactants.0, definition.reactants.1);
+            validate_reaction(&label, definition)?;
+
+            let reaction = Reaction {
+                reactants: (resolve(&label, &definition.reactants.0)?, resolve(&label, &definition.reactants.1)?),
+                products: (resolve(&label, &definition.products.0)?, resolve(&label, &definition.products.1)?),
+                probability: definition.probability,
+                temp_range: definition.temp_range,
+                heat: definition.heat,
+            };
+
+            // both orders point at the same reaction, the bool remembers which one was asked for
+            let (a, b) = reaction.reactants;
+            if reaction_lookup.contains_key(&(a, b)) { return Err(MaterialError::DuplicateReaction(definition.reactants.0.clone(), definition.reactants.1.clone())) }
+            reaction_lookup.insert((a, b), (reactions.len(), false));
+            reaction_lookup.insert((b, a), (reactions.len(), a != b));
+            reactions.push(reaction);
+        }
+
+        // every id was handed out exactly once above, so there are no gaps
+        let materials = materials.into_iter().map(|material| material.expect("every material id is assigned")).collect();
+        Ok(MaterialRegistry { materials, reactions, reaction_lookup })
+    }
+
+    /// # Functionality:
+    /// Returns all materials, indexed by their ```CellType``` id
+    pub fn materials(&self) -> &[CellTypeProperties] { &self.materials }
+
+    /// # Functionality:
+    /// Returns the id of the material called ```name```
+    #[allow(dead_code)]
+    pub fn find(&self, name: &str) -> Option<CellType> {
+        self.materials.iter().find(|material| material.name == name).map(|material| material.cell_type)
+    }
+
+    /// # Functionality:
+    /// Returns the reaction between a and b together with a bool that is true if a and b are swapped relative to ```reactants```
+    pub fn find_reaction(&self, a: CellType, b: CellType) -> Option<(&Reaction, bool)> {
+        self.reaction_lookup.get(&(a, b)).map(|(index, swapped)| (&self.reactions[*index], *swapped))
+    }
+}
+
+/// # Functionality:
+/// Checks that all numbers of a material are within their valid range
+fn validate_material(definition: &MaterialDefinition) -> Result<(), MaterialError> {
+    let invalid = |field, reason| Err(MaterialError::InvalidValue { material: definition.name.clone(), field, reason });
+
+    if definition.name.is_empty() { return invalid("name", "must not be empty") }
+    if !(definition.density >= 0.0 && definition.density.is_finite()) { return invalid("density", "must be a finite number of at least 0.0") }
+    if !(definition.temp_coefficient >= 0.0 && definition.temp_coefficient.is_finite()) { return invalid("temp_coefficient", "must be a finite number of at least 0.0") }
+
+    if definition.dispersion as i32 > UPDATE_REACH { return invalid("dispersion", "must not be larger than UPDATE_REACH (32)") }
+
+    if let Some((min, max)) = definition.lifetime {
+        if min == 0 || min > max { return invalid("lifetime", "must be a range (min, max) with 1 <= min <= max") }
+    }
+
+    if let Some(strength) = definition.support_strength {
+        if definition.state != StateOfAggregation::ImmovableSolid { return invalid("support_strength", "is only supported for immovable solids") }
+        if strength == 0 || strength as i32 >= STRUCTURE_REACH { return invalid("support_strength", "must be between 1 and STRUCTURE_REACH - 1 (31)") }
+    }
+
+    if definition.moisture_capacity > 0 && definition.state != StateOfAggregation::Granular { return invalid("moisture_capacity", "is only supported for granular materials") }
+    if definition.soaked_into.is_some() && definition.moisture_capacity == 0 { return invalid("soaked_into", "needs a moisture_capacity") }
+    if definition.electric != Electric::Insulator && definition.state != StateOfAggregation::ImmovableSolid { return invalid("electric", "is only supported for immovable solids") }
+    if matches!(definition.electric, Electric::Switch { .. }) != definition.toggles_into.is_some() { return invalid("toggles_into", "is needed by switches and only supported for them") }
+    if definition.emits.is_some() && definition.state != StateOfAggregation::ImmovableSolid { return invalid("emits", "is only supported for immovable solids") }
+    if definition.consumes != Consumes::Nothing && definition.state != StateOfAggregation::ImmovableSolid { return invalid("consumes", "is only supported for immovable solids") }
+    if definition.emits.as_ref().is_some_and(|emission| !(0.0..=1.0).contains(&emission.rate)) { return invalid("emits", "needs a rate between 0.0 and 1.0") }
+
+    if let Texture::Grain { spacing, strength } | Texture::Strata { spacing, strength } = definition.texture {
+        if !(spacing > 0.0 && spacing.is_finite()) { return invalid("texture", "needs a finite spacing above 0.0") }
+        if !(0.0..=1.0).contains(&strength) { return invalid("texture", "needs a strength between 0.0 and 1.0") }
+    }
+
+    for (field, value) in [("hardness", definition.hardness), ("corrosion_resistance", definition.corrosion_resistance), ("burn_rate", definition.burn_rate), ("viscosity", definition.viscosity), ("color_variation", definition.color_variation)] {
+        if !(0.0..=1.0).contains(&value) { return invalid(field, "must be between 0.0 and 1.0") }
+    }
+    Ok(())
+}
+
+/// # Functionality:
+/// Checks that the probability and temperature range of a reaction make sense
+fn validate_reaction(label: &str, definition: &ReactionDefinition) -> Result<(), MaterialError> {
+    let invalid = |field, reason| Err(MaterialError::InvalidValue { material: label.to_string(), field, reason });
+
+    if !(0.0..=1.0).contains(&definition.probability) { return invalid("probability", "must be between 0.0 and 1.0") }
+    if definition.temp_range.0 > definition.temp_range.1 { return invalid("temp_range", "must not start above its end") }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// returns a material file that defines every builtin material as plain air, followed by ```extra_materials``` and the reactions of ```reactions```
+    fn material_file(extra_materials: &str, reactions: &str) -> String {
+        let builtins: String = BUILTIN_MATERIALS.iter().map(|name| format!("(name: \"{name}\", state: Replaceable, density: 0.0, base_color: (0, 0, 0, 0)),\n")).collect();
+        format!("(materials: [\n{builtins}{extra_materials}], reactions: [{reactions}])")
+    }
+
+    /// loads ```source``` and returns the error it was rejected with
+    fn rejection(source: &str) -> MaterialError {
+        match MaterialRegistry::from_ron(source) {
+            Ok(_) => panic!("the material file was accepted"),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn the_minimal_file_is_valid() {
+        let registry = MaterialRegistry::from_ron(&material_file("", "")).unwrap();
+        assert_eq!(registry.materials().len(), BUILTIN_MATERIALS.len());
+    }
+
+    #[test]
+    fn reactions_with_unknown_materials_are_rejected() {
+        let err = rejection(&material_file("", r#"(reactants: ("Water", "Slime"), products: ("Steam", "Air"), probability: 0.5)"#));
+
+        assert!(matches!(&err, MaterialError::UnknownMaterial { referenced_by, name } if referenced_by == "the reaction between 'Water' and 'Slime'" && name == "Slime"), "{err:?}");
+        assert_eq!(err.to_string(), "'the reaction between 'Water' and 'Slime'' references the material 'Slime', which is not defined");
+    }
+
+    #[test]
+    fn duplicate_materials_are_rejected() {
+        let err = rejection(&material_file(r#"(name: "Sand", state: Granular, density: 1.5, base_color: (0, 0, 0, 0)),"#, ""));
+
+        assert!(matches!(&err, MaterialError::DuplicateMaterial(name) if name == "Sand"), "{err:?}");
+        assert_eq!(err.to_string(), "the material 'Sand' is defined more than once");
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected() {
+        let err = rejection(&material_file(r#"(name: "Slime", state: Liquid, density: -1.0, base_color: (0, 0, 0, 0)),"#, ""));
+        assert!(matches!(&err, MaterialError::InvalidValue { material, field: "density", .. } if material == "Slime"), "{err:?}");
+        assert_eq!(err.to_string(), "the field 'density' of 'Slime' must be a finite number of at least 0.0");
+
+        let err = rejection(&material_file("", r#"(reactants: ("Water", "Lava"), products: ("Steam", "Rock"), probability: 0.5, temp_range: (500, 400))"#));
+        assert!(matches!(&err, MaterialError::InvalidValue { field: "temp_range", .. }), "{err:?}");
+        assert_eq!(err.to_string(), "the field 'temp_range' of 'the reaction between 'Water' and 'Lava'' must not start above its end");
+
+        // temperatures are kept in whole Kelvin, so anything below 0 does not even parse
+        let err = rejection(&material_file(r#"(name: "Slime", state: Liquid, density: 1.0, base_temp: -5, base_color: (0, 0, 0, 0)),"#, ""));
+        assert!(matches!(err, MaterialError::Parse(_)), "{err:?}");
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        let err = rejection("(materials: [(name: \"Air\", state: Replaceable, density 0.0)])");
+
+        assert!(matches!(err, MaterialError::Parse(_)), "{err:?}");
+        assert_eq!(err.to_string(), "the material file is malformed at 1:56: Expected colon");
+    }
+
+    #[test]
+    fn builtin_materials_match_the_material_file() {
+        let registry = MaterialRegistry::from_ron(DEFAULT_MATERIALS).unwrap();
+
+        // every builtin keeps its fixed id, which the constants on CellType rely on
+        for (id, name) in BUILTIN_MATERIALS.iter().enumerate() {
+            assert_eq!(registry.materials()[id].name, *name);
+            assert_eq!(registry.find(name), Some(CellType(id as u16)));
+        }
+        let constants = [
+            CellType::AIR, CellType::ROCK, CellType::WATER, CellType::SAND, CellType::GRAVEL, CellType::WOOD, CellType::STEAM, CellType::GUNPOWDER, CellType::OIL,
+            CellType::LAVA, CellType::ACID, CellType::GLASS, CellType::ICE, CellType::FIRE, CellType::SMOKE, CellType::ASH, CellType::TOXIC_GAS,
+        ];
+        for (constant, name) in constants.iter().zip(BUILTIN_MATERIALS) {
+            assert_eq!(registry.materials()[constant.0 as usize].name, name);
+        }
+    }
+}
@@ -0,0 +1,218 @@
+// imports:
+use rand::Rng;
+
+use crate::cells_layer::{Cell, CellType, Space};
+use crate::gravity_layer::Gravity;
+use crate::texture_layer::shade;
+
+/// The ```moisture``` that a single water cell is worth
+pub const WATER_MOISTURE: u8 = 64;
+
+/// The chance per tick and neighbouring water cell that an absorbent cell with enough room soaks it up
+const SOAK_CHANCE: f32 = 0.1;
+
+/// The fraction of the difference in ```moisture``` that a cell passes on to a drier neighbour per tick
+const WICKING: f32 = 0.25;
+
+/// The temperature in ```Kelvin``` from which wet cells dry out
+const DRYING_TEMP: u16 = 373;
+
+/// The heat in ```Kelvin``` that a cell loses for every ```WATER_MOISTURE``` that evaporates from it
+const DRYING_HEAT: u16 = 40;
+
+/// The chance per tick that a soaked through granular cell does not slide off, it scales with how wet the cell is
+const WET_COHESION: f32 = 0.9;
+
+/// How much darker a cell with the highest possible ```moisture``` is drawn
+const WET_DARKENING: f32 = 0.5;
+
+impl Cell {
+
+    /// # Functionality:
+    /// Returns the colour that the cell is drawn with
+    /// # Behaviour:
+    /// Wet cells are darker, up to ```WET_DARKENING``` at a ```moisture``` of 255
+    pub fn display_color(&self) -> [u8; 4] {
+        shade(self.color, 1.0 - WET_DARKENING * self.moisture as f32 / u8::MAX as f32)
+    }
+}
+
+impl Space {
+
+    /// # Functionality:
+    /// Returns the chance that the cell at index i holds on to its neighbours instead of sliding off
+    /// # Formula:
+    /// ```
+    /// WET_COHESION * moisture / moisture_capacity
+    /// ```
+    pub fn cohesion(&self, i: usize) -> f32 {
+        let capacity = self.cells[i].get_cell_properties().moisture_capacity;
+        if capacity == 0 { return 0.0 }
+        WET_COHESION * self.cells[i].moisture as f32 / capacity as f32
+    }
+
+    /// # Functionality:
+    /// Returns whether the cell at index i could slide diagonally down, seen from ```gravity```, see ```try_move_diagonally()```
+    pub fn could_slide(&self, i: isize, gravity: Gravity) -> bool {
+        [gravity.rotate(1), gravity.rotate(-1)].into_iter().any(|direction| {
+            self.get_neighbour(i, direction).is_some_and(|j| !self.cuts_corner(i, direction, gravity) && self.can_displace(i, j, false))
+        })
+    }
+
+    /// # Functionality:
+    /// Lets the cell at index i, which has a ```moisture_capacity```, soak up water, pass its moisture on and dry out
+    /// # Behaviour:
+    /// A cell at ```DRYING_TEMP``` or hotter gives off ```WATER_MOISTURE``` per tick as ```CellType::STEAM``` into a neighbouring air cell and cools down by ```DRYING_HEAT``` for it.
+    /// Otherwise it soaks up neighbouring water cells as long as it has room for a whole one, see ```SOAK_CHANCE```, and passes a part of its moisture on to drier absorbent neighbours,
+    /// see ```WICKING```. A cell that is soaked through turns into its ```soaked_into```, for example sand into mud. Such a cell soaks up water as long as it has any room left
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn update_moisture(&mut self, i: usize) {
+        let properties = self.cells[i].get_cell_properties();
+
+        if self.cells[i].moisture > 0 && self.cells[i].temp >= DRYING_TEMP {
+            self.mark_dirty(i);
+
+            // the steam needs somewhere to go
+            let free: Vec<usize> = self.get_neighbours(i).into_iter().flatten().filter(|j| self.cells[*j].cell_type == CellType::AIR).collect();
+            if free.is_empty() { return }
+
+            // a partial amount still evaporates, it just does not always make a whole steam cell
+            let amount = self.cells[i].moisture.min(WATER_MOISTURE);
+            let temp = self.cells[i].temp;
+            self.cells[i].moisture -= amount;
+            self.cells[i].temp = temp.saturating_sub((DRYING_HEAT as u32 * amount as u32 / WATER_MOISTURE as u32) as u16);
+            if self.rng.gen_range(0..WATER_MOISTURE) < amount {
+                let j = free[self.rng.gen_range(0..free.len())];
+                self.convert_cell(j, CellType::STEAM, temp);
+            }
+            return;
+        }
+
+        for j in self.get_neighbours(i).into_iter().flatten() {
+            let moisture = self.cells[i].moisture;
+            let room = properties.moisture_capacity.saturating_sub(moisture);
+
+            // a cell that turns into something else once it is soaked through takes up whatever does not fit as well
+            if self.cells[j].cell_type == CellType::WATER && (room >= WATER_MOISTURE || (room > 0 && properties.soaked_into.is_some())) {
+
+                // water next to a cell with room keeps the chunk awake until it is soaked up
+                self.mark_dirty(i);
+                if self.rng.gen::<f32>() < SOAK_CHANCE {
+                    self.cells[i].moisture += room.min(WATER_MOISTURE);
+                    let temp = self.cells[j].temp;
+                    self.convert_cell(j, CellType::AIR, temp);
+
+                    // soaked through, nothing is passed on anymore
+                    if self.cells[i].moisture >= properties.moisture_capacity { break }
+                }
+            } else {
+                let amount = wicked_moisture(moisture, self.cells[j].moisture, self.cells[j].get_cell_properties().moisture_capacity);
+                if amount == 0 { continue }
+
+                self.cells[i].moisture -= amount;
+                self.cells[j].moisture += amount;
+                self.mark_dirty(i);
+                self.mark_dirty(j);
+            }
+        }
+
+        if self.cells[i].moisture >= properties.moisture_capacity {
+            if let Some(into) = properties.soaked_into { self.convert_cell(i, into, self.cells[i].temp) }
+        }
+    }
+}
+
+/// # Functionality:
+/// Returns how much of its ```moisture``` a cell passes on to a neighbour that holds ```neighbour``` and can hold ```capacity``` at most
+/// # Behaviour:
+/// Only a neighbour that is drier by more than one takes anything. It takes ```WICKING``` of the difference, at least 1 and never more than it has room for
+pub fn wicked_moisture(moisture: u8, neighbour: u8, capacity: u8) -> u8 {
+    if moisture <= neighbour.saturating_add(1) { return 0 }
+    let room = capacity.saturating_sub(neighbour);
+    (((moisture - neighbour) as f32 * WICKING) as u8).max(1).min(room)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material_registry::MaterialRegistry;
+    use crate::test_support::{cell_at, find, material, place, tick};
+
+    #[test]
+    fn water_soaks_away_into_sand() {
+        let mut space = Space::with_seed(8, 12, 0);
+        place(&mut space, CellType::SAND, (0..8).flat_map(|x| (8..12).map(move |y| (x, y))));
+        place(&mut space, CellType::WATER, (0..8).map(|x| (x, 7)));
+        for _ in 0..400 { tick(&mut space) }
+
+        let sand = find(&space, CellType::SAND);
+        assert!(find(&space, CellType::WATER).is_empty());
+        assert_eq!(sand.len(), 32);
+        assert_eq!(sand.iter().map(|&p| cell_at(&space, p).moisture as u32).sum::<u32>(), 8 * WATER_MOISTURE as u32);
+        assert!(sand.iter().all(|&p| cell_at(&space, p).moisture > 0));
+    }
+
+    #[test]
+    fn soaked_sand_turns_into_mud() {
+        let mut space = Space::with_seed(4, 12, 0);
+        place(&mut space, CellType::SAND, (0..4).map(|x| (x, 11)));
+        place(&mut space, CellType::WATER, (0..4).flat_map(|x| (3..11).map(move |y| (x, y))));
+        for _ in 0..600 { tick(&mut space) }
+
+        // the wet sand shares its water, so every sand cell takes three or four water cells with it
+        let mud = material("Mud");
+        assert!(find(&space, CellType::SAND).is_empty());
+        assert_eq!(find(&space, mud).len(), 4);
+        assert!((32 - 16..=32 - 12).contains(&find(&space, CellType::WATER).len()), "{}", find(&space, CellType::WATER).len());
+    }
+
+    #[test]
+    fn wet_sand_dries_out_into_steam() {
+        let mut space = Space::with_seed(3, 3, 0);
+        let i = space.get_index(1, 2) as usize;
+        let cell = Cell { moisture: 128, temp: 600, ..Cell::build_cell(CellType::SAND, &mut space.rng) };
+        space.set_cell(i, &cell);
+        for _ in 0..2 { space.update_cell_alchemy() }
+
+        assert_eq!(space.cells[i].moisture, 0);
+        assert_eq!(space.cells[i].temp, 600 - 2 * DRYING_HEAT);
+        assert_eq!(find(&space, CellType::STEAM).len(), 2);
+    }
+
+    #[test]
+    fn wet_sand_piles_up_steeper() {
+        let pour = |moisture: u8| {
+            let mut space = Space::with_seed(48, 32, 0);
+            for _ in 0..200 {
+                let i = space.get_index(24, 0) as usize;
+                if space.cells[i].cell_type == CellType::AIR {
+                    let cell = Cell { moisture, ..Cell::build_cell(CellType::SAND, &mut space.rng) };
+                    space.set_cell(i, &cell);
+                }
+                space.update_cell_behaviour();
+            }
+            for _ in 0..200 { space.update_cell_behaviour() }
+
+            // the width of the base of the pile
+            (0..48).filter(|&x| space.cells[space.get_index(x, 31) as usize].cell_type == CellType::SAND).count()
+        };
+        assert!(pour(160) < pour(0), "wet: {}, dry: {}", pour(160), pour(0));
+    }
+
+    #[test]
+    fn cells_that_hold_the_most_moisture_share_it_without_overflowing() {
+
+        // a test-local copy of the materials in which sand holds as much moisture as a cell can
+        let source = include_str!("../materials.ron").replacen("moisture_capacity: 192", "moisture_capacity: 255", 1);
+        let registry = MaterialRegistry::from_ron(&source).unwrap();
+        let capacity = registry.materials()[CellType::SAND.0 as usize].moisture_capacity;
+        assert_eq!(capacity, u8::MAX);
+
+        // nothing flows towards a cell that is as wet as a cell can get, but such a cell passes its moisture on
+        assert_eq!(wicked_moisture(100, u8::MAX, capacity), 0);
+        assert_eq!(wicked_moisture(u8::MAX, u8::MAX, capacity), 0);
+        assert_eq!(wicked_moisture(u8::MAX, 100, capacity), ((u8::MAX - 100) as f32 * WICKING) as u8);
+        assert_eq!(wicked_moisture(u8::MAX, u8::MAX - 2, capacity), 1);
+    }
+}
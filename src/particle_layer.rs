@@ -0,0 +1,162 @@
+// imports:
+use crate::cells_layer::{Cell, CellType, Space, StateOfAggregation, GRAVITY, MAX_VELOCITY, UPDATE_REACH};
+
+/// # Functionality:
+/// A cell that left the grid and flies freely
+/// # Structure:
+/// ```
+/// pub struct Particle {
+///     pub cell: Cell,
+///     pub position: (f32, f32),
+///     pub velocity: (f32, f32),
+/// }
+/// ```
+/// ```position``` is given in the coordinates of ```Space``` and ```velocity``` in cells per tick. The ```cell``` keeps its type, colour and temperature while it flies
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Particle {
+    pub cell: Cell,
+    pub position: (f32, f32),
+    pub velocity: (f32, f32),
+}
+
+/// # Functionality:
+/// Holds every ```Particle``` of a ```Space```
+/// # Structure:
+/// ```
+/// pub struct ParticleLayer {
+///     pub particles: Vec<Particle>,
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ParticleLayer {
+    pub particles: Vec<Particle>,
+}
+
+impl Space {
+    /// # Functionality:
+    /// Takes the cell at index i out of the grid and lets it fly off with ```velocity```. Air is left behind
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn eject_cell(&mut self, i: usize, velocity: (f32, f32)) {
+        let (x, y) = self.get_coordinates(i as isize);
+        let cell = self.cells[i];
+        self.particles.particles.push(Particle { cell, position: (x as f32, y as f32), velocity });
+
+        let temp = self.cells[i].temp;
+        self.convert_cell(i, CellType::AIR, temp);
+    }
+
+    /// # Functionality:
+    /// Moves every particle along its path and puts it back into the grid once it hits something
+    /// # Behaviour:
+    /// Particles fly through air, gases and everything ```StateOfAggregation::Replaceable```, accelerated by ```GRAVITY``` in the direction it has where they are. They move in steps of at most one cell,
+    /// so that they can not skip over anything, and settle in the last cell before an obstacle or the border of the space, see ```settle_particle()```
+    pub fn update_particles(&mut self) {
+        let particles = std::mem::take(&mut self.particles.particles);
+
+        for mut particle in particles {
+            let gravity = self.particle_cell(particle.position).map_or(self.gravity.direction, |i| self.gravity_at(i)).unit();
+            particle.velocity.0 = (particle.velocity.0 + gravity.0 * GRAVITY).clamp(-MAX_VELOCITY, MAX_VELOCITY);
+            particle.velocity.1 = (particle.velocity.1 + gravity.1 * GRAVITY).clamp(-MAX_VELOCITY, MAX_VELOCITY);
+
+            let steps = particle.velocity.0.abs().max(particle.velocity.1.abs()).ceil().max(1.0);
+            let step = (particle.velocity.0 / steps, particle.velocity.1 / steps);
+
+            let mut flying = true;
+            for _ in 0..steps as i32 {
+                let next = (particle.position.0 + step.0, particle.position.1 + step.1);
+
+                // the border of the space is a wall, just like it is for the cells
+                let passable = self.particle_cell(next).is_some_and(|j| self.is_passable(j));
+                if !passable {
+                    flying = !self.settle_particle(particle);
+
+                    // a particle that found no place waits where it is and tries again in the next tick
+                    particle.velocity = (0.0, 0.0);
+                    break;
+                }
+                particle.position = next;
+            }
+
+            if flying { self.particles.particles.push(particle) }
+        }
+    }
+
+    /// # Functionality:
+    /// Returns the index of the cell that contains ```position```, or ```None``` if it is outside of the space
+    pub fn particle_cell(&self, position: (f32, f32)) -> Option<usize> {
+        let (x, y) = (position.0.round() as i32, position.1.round() as i32);
+        if x < 0 || y < 0 || x >= self.width || y >= self.height { return None }
+        Some(self.get_index(x, y) as usize)
+    }
+
+    /// # Functionality:
+    /// Returns whether a particle can fly through the cell at index i
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn is_passable(&self, i: usize) -> bool {
+        matches!(self.cells[i].get_cell_properties().state, StateOfAggregation::Gas | StateOfAggregation::Replaceable)
+    }
+
+    /// # Functionality:
+    /// Puts ```particle``` back into the grid at its position, keeping its speed along gravity as the velocity of the cell. Returns a sucess bool
+    /// # Behaviour:
+    /// Only air is replaced. If something else moved into that cell the particle settles in the closest air cell above it instead, seen from the local gravity,
+    /// as long as there is nothing immovable in between and it is at most ```UPDATE_REACH``` cells away
+    fn settle_particle(&mut self, particle: Particle) -> bool {
+        let Some(mut i) = self.particle_cell(particle.position) else { return false };
+        let gravity = self.gravity_at(i);
+
+        for _ in 0..UPDATE_REACH {
+            if self.cells[i].cell_type == CellType::AIR {
+                self.set_cell(i, &Cell { velocity: (0.0, gravity.along(particle.velocity).max(0.0)), ..particle.cell });
+                return true;
+            }
+            if self.cells[i].get_cell_properties().state == StateOfAggregation::ImmovableSolid { return false }
+            let Some(above) = self.get_neighbour(i as isize, gravity.opposite()) else { return false };
+            i = above as usize;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cell_at, find, place};
+
+    #[test]
+    fn ejected_cells_fly_and_settle_back_into_the_grid() {
+        let mut space = Space::with_seed(32, 32, 0);
+        place(&mut space, CellType::ROCK, (0..32).map(|x| (x, 31)));
+        let sand = Cell { temp: 500, ..Cell::build_cell(CellType::SAND, &mut space.rng) };
+        let i = space.get_index(4, 20) as usize;
+        space.set_cell(i, &sand);
+
+        space.eject_cell(i, (1.0, -3.0));
+        assert_eq!(space.cells[i].cell_type, CellType::AIR);
+        assert_eq!(space.particles.particles.len(), 1);
+
+        for _ in 0..40 { space.update_particles() }
+
+        // it flew off to the side and landed on the rock, the same cell as before
+        let landed = find(&space, CellType::SAND);
+        assert!(space.particles.particles.is_empty());
+        assert_eq!(landed.len(), 1);
+        assert!(landed[0].0 > 10 && landed[0].1 == 30, "{landed:?}");
+        assert_eq!(cell_at(&space, landed[0]).color, sand.color);
+        assert_eq!(cell_at(&space, landed[0]).temp, 500);
+    }
+
+    #[test]
+    fn particles_settle_on_top_of_what_took_their_place() {
+        let mut space = Space::with_seed(8, 8, 0);
+        let cell = Cell::build_cell(CellType::WATER, &mut space.rng);
+        space.particles.particles.push(Particle { cell, position: (4.0, 7.0), velocity: (0.0, 1.0) });
+        place(&mut space, CellType::SAND, [(4, 7), (4, 6)]);
+        space.update_particles();
+
+        assert!(space.particles.particles.is_empty());
+        assert_eq!(find(&space, CellType::WATER), [(4, 5)]);
+    }
+}
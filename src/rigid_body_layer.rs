@@ -0,0 +1,400 @@
+// imports:
+use std::collections::HashSet;
+
+use crate::cells_layer::{Cell, CellType, Space, StateOfAggregation, GRAVITY, MAX_VELOCITY, UPDATE_REACH};
+use crate::gravity_layer::Gravity;
+use crate::particle_layer::Particle;
+
+/// Groups of solid cells with more cells than this count as anchored, so that digging through a mountain does not turn all of it into a body
+pub const MAX_BODY_CELLS: usize = 2048;
+
+/// Groups of solid cells that reach further than this from where they lost their hold count as anchored as well, which keeps the search around a removed solid local
+pub const MAX_BODY_REACH: i32 = UPDATE_REACH;
+
+/// The angle in radians that a body tips by per tick while its centre hangs over the edge of what it rests on
+const TIP_SPEED: f32 = 0.08;
+
+/// # Functionality:
+/// A group of solid cells that lost its hold and moves as one piece
+/// # Structure:
+/// ```
+/// pub struct RigidBody {
+///     pub cells: Vec<((f32, f32), Cell)>,
+///     pub position: (f32, f32),
+///     pub velocity: (f32, f32),
+///     pub angle: f32,
+///     pub angular_velocity: f32,
+/// }
+/// ```
+/// Every cell is stored with its offset from the centre of the body at an ```angle``` of 0.0. ```position``` is the centre in the coordinates of ```Space```,
+/// ```velocity``` is given in cells per tick and ```angle``` and ```angular_velocity``` in radians and radians per tick
+#[derive(Clone, Debug, PartialEq)]
+pub struct RigidBody {
+    pub cells: Vec<((f32, f32), Cell)>,
+    pub position: (f32, f32),
+    pub velocity: (f32, f32),
+    pub angle: f32,
+    pub angular_velocity: f32,
+}
+
+impl RigidBody {
+
+    /// # Functionality:
+    /// Returns where the cells of the body would be if its centre was at ```position``` and it was turned by ```angle```, in the same order as ```cells```
+    pub fn cell_positions(&self, position: (f32, f32), angle: f32) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.cells.iter().map(move |(offset, _)| {
+            let (x, y) = rotate(*offset, angle);
+            (position.0 + x, position.1 + y)
+        })
+    }
+
+    /// # Functionality:
+    /// Returns the average density of the cells of the body
+    pub fn density(&self) -> f32 {
+        self.cells.iter().map(|(_, cell)| cell.get_cell_properties().density).sum::<f32>() / self.cells.len() as f32
+    }
+}
+
+/// # Functionality:
+/// Turns the vector ```(x, y)``` by ```angle``` radians around the origin
+fn rotate((x, y): (f32, f32), angle: f32) -> (f32, f32) {
+    let (sin, cos) = angle.sin_cos();
+    (x * cos - y * sin, x * sin + y * cos)
+}
+
+/// # Functionality:
+/// Holds every ```RigidBody``` of a ```Space```
+/// # Structure:
+/// ```
+/// pub struct RigidBodyLayer {
+///     pub bodies: Vec<RigidBody>,
+///     pub removed_solids: Vec<usize>,
+/// }
+/// ```
+/// ```removed_solids``` are the indices of the solid cells that were replaced by something else since the last tick, see ```note_removed_solid()```
+#[derive(Clone, Debug, Default)]
+pub struct RigidBodyLayer {
+    pub bodies: Vec<RigidBody>,
+    pub removed_solids: Vec<usize>,
+}
+
+impl Space {
+
+    /// # Functionality:
+    /// Remembers the cell at index i if it is ```StateOfAggregation::ImmovableSolid``` and is about to be replaced by ```cell```, which is not
+    /// # Behaviour:
+    /// Has to be called before the cell is replaced, the surroundings of every remembered cell are checked for their hold at the end of the tick, see ```Space::update_cell_behaviour()```
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn note_removed_solid(&mut self, i: usize, cell: &Cell) {
+        let solid = |cell: &Cell| cell.get_cell_properties().state == StateOfAggregation::ImmovableSolid;
+        if solid(&self.cells[i]) && !solid(cell) { self.bodies.removed_solids.push(i) }
+    }
+
+    /// # Functionality:
+    /// Moves every body, putting it back into the grid once it comes to rest
+    /// # Behaviour:
+    /// Bodies fall with ```GRAVITY``` in the direction it has at their centre and move in steps of at most one cell. A body slides along whatever blocks only one direction of its path
+    /// and keeps spinning until the turn would make it overlap something. Once it rests on something it tips over the edge if its centre is not above what holds it, see ```tipping_pivot()```,
+    /// and settles back into the grid otherwise, see ```settle_body()```
+    pub fn update_rigid_bodies(&mut self) {
+        let bodies = std::mem::take(&mut self.bodies.bodies);
+        for mut body in bodies {
+            let gravity = self.particle_cell(body.position).map_or(self.gravity.direction, |i| self.gravity_at(i));
+            let pull = gravity.unit();
+            body.velocity.0 = (body.velocity.0 + pull.0 * GRAVITY).clamp(-MAX_VELOCITY, MAX_VELOCITY);
+            body.velocity.1 = (body.velocity.1 + pull.1 * GRAVITY).clamp(-MAX_VELOCITY, MAX_VELOCITY);
+
+            // a spin stops as soon as it hits something
+            if body.angular_velocity != 0.0 {
+                let angle = body.angle + body.angular_velocity;
+                if self.body_fits(&body, body.position, angle) { body.angle = angle } else { body.angular_velocity = 0.0 }
+            }
+
+            let steps = body.velocity.0.abs().max(body.velocity.1.abs()).ceil().max(1.0);
+            let step = (body.velocity.0 / steps, body.velocity.1 / steps);
+            for _ in 0..steps as i32 {
+                let (x, y) = body.position;
+                if self.body_fits(&body, (x + step.0, y + step.1), body.angle) {
+                    body.position = (x + step.0, y + step.1);
+                } else if step.0 != 0.0 && self.body_fits(&body, (x + step.0, y), body.angle) {
+                    body.position = (x + step.0, y);
+                    body.velocity.1 = 0.0;
+                } else if step.1 != 0.0 && self.body_fits(&body, (x, y + step.1), body.angle) {
+                    body.position = (x, y + step.1);
+                    body.velocity.0 = 0.0;
+                } else {
+                    body.velocity = (0.0, 0.0);
+                    break;
+                }
+            }
+
+            // a body that can still fall keeps flying
+            let (dx, dy) = gravity.offset();
+            if self.body_fits(&body, (body.position.0 + dx as f32, body.position.1 + dy as f32), body.angle) {
+                self.bodies.bodies.push(body);
+                continue;
+            }
+
+            if let Some((pivot, direction)) = self.tipping_pivot(&body, gravity) {
+                let turn = direction * TIP_SPEED;
+                let (x, y) = rotate((body.position.0 - pivot.0, body.position.1 - pivot.1), turn);
+                let position = (pivot.0 + x, pivot.1 + y);
+
+                if self.body_fits(&body, position, body.angle + turn) {
+                    body.position = position;
+                    body.angle += turn;
+                    body.angular_velocity = turn;
+                    self.bodies.bodies.push(body);
+                    continue;
+                }
+            }
+            self.settle_body(body, gravity);
+        }
+    }
+
+    /// # Functionality:
+    /// Lifts every group of solid cells next to one of the ```removed``` cells out of the grid as a ```RigidBody```, unless the group is anchored
+    /// # Behaviour:
+    /// A group is made of the ```StateOfAggregation::ImmovableSolid``` cells that are connected through their direct neighbours, no matter their ```CellType```.
+    /// It is anchored if it rests on the border of the space that its gravity points at, which is the floor of the world, has more than ```MAX_BODY_CELLS``` cells
+    /// or reaches further than ```MAX_BODY_REACH``` cells from the removed cell in any direction. The other borders are walls, a group that only leans against them still falls.
+    /// Because of the reach, whether a group falls only depends on the cells around it, so a ```World``` that keeps enough cells loaded around its simulated area never sees its border here
+    pub fn detach_floating_solids(&mut self, removed: &[usize]) {
+        if removed.is_empty() { return }
+
+        let mut visited = vec![false; self.lenght as usize];
+        for &i in removed {
+            for j in self.get_neighbours(i).into_iter().flatten() {
+                if visited[j] || self.cells[j].get_cell_properties().state != StateOfAggregation::ImmovableSolid { continue }
+                if let Some(group) = self.floating_group(j, &mut visited) { self.lift_body(&group) }
+            }
+        }
+    }
+
+    /// # Functionality:
+    /// Returns the indices of the solid group that the cell at index i belongs to, or ```None``` if the group is anchored, see ```detach_floating_solids()```
+    /// # Behaviour:
+    /// The flood fill stops as soon as the group turns out to be anchored. Every cell that was reached is marked in ```visited```.
+    /// Floating groups are lifted out of the grid right away, so a solid that an earlier flood fill reached belongs to an anchored group and anchors this one as well
+    fn floating_group(&self, i: usize, visited: &mut [bool]) -> Option<Vec<usize>> {
+        let start = self.get_coordinates(i as isize);
+        let mut found = vec![i];
+        let mut group = HashSet::from([i]);
+        visited[i] = true;
+
+        // depth first flood fill, found doubles as the result
+        let mut stack = vec![i];
+        while let Some(j) = stack.pop() {
+            let on_floor = self.get_neighbour(j as isize, self.gravity_at(j)).is_none();
+            let (x, y) = self.get_coordinates(j as isize);
+            let too_far = (x - start.0).abs() > MAX_BODY_REACH || (y - start.1).abs() > MAX_BODY_REACH;
+            if on_floor || too_far || found.len() > MAX_BODY_CELLS { return None }
+
+            for k in self.get_neighbours(j).into_iter().flatten() {
+                if group.contains(&k) || self.cells[k].get_cell_properties().state != StateOfAggregation::ImmovableSolid { continue }
+                if visited[k] { return None }
+
+                visited[k] = true;
+                group.insert(k);
+                found.push(k);
+                stack.push(k);
+            }
+        }
+        Some(found)
+    }
+
+    /// # Functionality:
+    /// Takes the cells of ```group``` out of the grid and adds them as a resting ```RigidBody``` around their centre. Air of the same temperature is left behind
+    /// # Panic behaviour:
+    /// Panics if one of the indices is out of bounds
+    fn lift_body(&mut self, group: &[usize]) {
+        let coordinates: Vec<(i32, i32)> = group.iter().map(|&j| self.get_coordinates(j as isize)).collect();
+        let centre = (
+            coordinates.iter().map(|c| c.0 as f32).sum::<f32>() / group.len() as f32,
+            coordinates.iter().map(|c| c.1 as f32).sum::<f32>() / group.len() as f32,
+        );
+
+        let mut cells = Vec::with_capacity(group.len());
+        for (&j, (x, y)) in group.iter().zip(coordinates) {
+            cells.push(((x as f32 - centre.0, y as f32 - centre.1), self.cells[j]));
+
+            // written directly, the cell did not stop being solid, it just left the grid
+            self.cells[j] = Cell { temp: self.cells[j].temp, generation: self.generation, ..Cell::set_air() };
+            self.mark_dirty(j);
+        }
+        self.bodies.bodies.push(RigidBody { cells, position: centre, velocity: (0.0, 0.0), angle: 0.0, angular_velocity: 0.0 });
+    }
+
+    /// # Functionality:
+    /// Returns whether ```body``` fits into the grid with its centre at ```position``` and turned by ```angle```
+    /// # Behaviour:
+    /// A body passes through everything that a ```Particle``` passes through, as well as liquids that are lighter than its average density. The border of the space is a wall
+    pub fn body_fits(&self, body: &RigidBody, position: (f32, f32), angle: f32) -> bool {
+        let density = body.density();
+        body.cell_positions(position, angle).all(|p| self.particle_cell(p).is_some_and(|j| {
+            let properties = self.cells[j].get_cell_properties();
+            self.is_passable(j) || (properties.state == StateOfAggregation::Liquid && properties.density < density)
+        }))
+    }
+
+    /// # Functionality:
+    /// Returns the point that a resting ```body``` tips over around and the direction of the turn, or ```None``` if it rests safely
+    /// # Behaviour:
+    /// The cells of the body that have something solid right below them, seen from ```gravity```, hold it up. As long as the centre is above them, within half a cell, the body rests.
+    /// Otherwise it turns around the holding cell that is closest to the centre, in the direction that gravity pulls the centre
+    fn tipping_pivot(&self, body: &RigidBody, gravity: Gravity) -> Option<((f32, f32), f32)> {
+        let positions: Vec<(f32, f32)> = body.cell_positions(body.position, body.angle).collect();
+        let rounded: Vec<(i32, i32)> = positions.iter().map(|p| (p.0.round() as i32, p.1.round() as i32)).collect();
+        let density = body.density();
+        let (dx, dy) = gravity.offset();
+        let side = gravity.rotate(-2).unit();
+
+        // how far every holding cell is from the centre, measured across gravity
+        let holds: Vec<((f32, f32), f32)> = positions.iter().zip(&rounded).filter(|(_, &(x, y))| {
+            let below = (x + dx, y + dy);
+            if rounded.contains(&below) { return false }
+            !self.get_index_checked(below.0, below.1).is_ok_and(|j| {
+                let properties = self.cells[j as usize].get_cell_properties();
+                self.is_passable(j as usize) || (properties.state == StateOfAggregation::Liquid && properties.density < density)
+            })
+        }).map(|(&p, _)| (p, side.0 * (p.0 - body.position.0) + side.1 * (p.1 - body.position.1))).collect();
+
+        let min = holds.iter().min_by(|a, b| a.1.total_cmp(&b.1))?;
+        let max = holds.iter().max_by(|a, b| a.1.total_cmp(&b.1))?;
+        if min.1 - 0.5 <= 0.0 && 0.0 <= max.1 + 0.5 { return None }
+
+        let pivot = if min.1 > 0.0 { min.0 } else { max.0 };
+        let (gx, gy) = gravity.unit();
+        let arm = (body.position.0 - pivot.0, body.position.1 - pivot.1);
+        Some((pivot, (arm.0 * gy - arm.1 * gx).signum()))
+    }
+
+    /// # Functionality:
+    /// Writes the cells of ```body``` back into the grid at their rounded positions
+    /// # Behaviour:
+    /// Liquids and gases in the way are moved to the closest air cell above the body, seen from ```gravity```, see ```make_room()```.
+    /// A cell whose place is taken by something else, for example another cell of the body after a turn, becomes a ```Particle``` instead, so nothing is lost
+    fn settle_body(&mut self, body: RigidBody, gravity: Gravity) {
+        let positions: Vec<(f32, f32)> = body.cell_positions(body.position, body.angle).collect();
+        for (position, (_, cell)) in positions.into_iter().zip(body.cells) {
+            match self.particle_cell(position) {
+                Some(j) if self.cells[j].cell_type == CellType::AIR => self.set_cell(j, &cell),
+                Some(j) if matches!(self.cells[j].get_cell_properties().state, StateOfAggregation::Liquid | StateOfAggregation::Gas) => {
+                    self.make_room(j, gravity);
+                    self.set_cell(j, &cell);
+                }
+                _ => self.particles.particles.push(Particle { cell, position, velocity: (0.0, 0.0) }),
+            }
+        }
+    }
+
+    /// # Functionality:
+    /// Moves the cell at index i into the closest air cell against ```gravity```, leaving air behind
+    /// # Behaviour:
+    /// Looks through everything in between, so water under a settling body rises to the surface. If there is no air up to the border of the space the cell is lost
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    fn make_room(&mut self, i: usize, gravity: Gravity) {
+        let cell = self.cells[i];
+        let mut j = i as isize;
+        while let Some(above) = self.get_neighbour(j, gravity.opposite()) {
+            j = above;
+            if self.cells[j as usize].cell_type == CellType::AIR {
+                self.set_cell(j as usize, &cell);
+                break;
+            }
+        }
+        self.set_cell(i, &Cell { temp: cell.temp, ..Cell::set_air() });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{find, material, place};
+
+    /// digs out the cell at ```(x, y)```
+    fn dig(space: &mut Space, (x, y): (i32, i32)) {
+        let i = space.get_index(x, y) as usize;
+        space.set_cell(i, &Cell::set_air());
+    }
+
+    #[test]
+    fn blocks_fall_once_they_lose_their_hold() {
+        let mut space = Space::with_seed(16, 24, 0);
+
+        // the block hangs from a ceiling that is held up by a wall
+        place(&mut space, CellType::ROCK, (0..24).map(|y| (0, y)).chain((1..7).map(|x| (x, 0))));
+        place(&mut space, CellType::ROCK, (0..4).map(|y| (7, y)));
+        place(&mut space, CellType::WOOD, (6..10).flat_map(|x| (4..7).map(move |y| (x, y))));
+        dig(&mut space, (7, 3));
+
+        space.update_cell_behaviour();
+        assert_eq!(space.bodies.bodies.len(), 1);
+        assert!(find(&space, CellType::WOOD).is_empty());
+
+        for _ in 0..60 { space.update_cell_behaviour() }
+        let wood = find(&space, CellType::WOOD);
+        assert!(space.bodies.bodies.is_empty());
+        assert_eq!(wood.len(), 12);
+        assert!(wood.iter().all(|&(x, y)| (6..10).contains(&x) && y >= 21), "{wood:?}");
+        assert_eq!(find(&space, CellType::ROCK).len(), 24 + 6 + 3);
+    }
+
+    #[test]
+    fn anchored_terrain_stays_in_place() {
+        let mut space = Space::with_seed(32, 32, 0);
+        place(&mut space, CellType::ROCK, (0..32).flat_map(|x| (16..32).map(move |y| (x, y))));
+        for (x, y) in (12..20).flat_map(|x| (18..22).map(move |y| (x, y))) { dig(&mut space, (x, y)) }
+        for _ in 0..20 { space.update_cell_behaviour() }
+
+        assert!(space.bodies.bodies.is_empty());
+        assert_eq!(find(&space, CellType::ROCK).len(), 32 * 16 - 8 * 4);
+    }
+
+    #[test]
+    fn bars_tip_over_the_edge_of_their_support() {
+        let mut space = Space::with_seed(24, 16, 0);
+        place(&mut space, CellType::ROCK, (9..16).map(|y| (4, y)));
+        place(&mut space, CellType::WOOD, (4..13).map(|x| (x, 8)));
+        dig(&mut space, (4, 9));
+        for _ in 0..150 { space.update_cell_behaviour() }
+
+        let wood = find(&space, CellType::WOOD);
+        assert!(space.bodies.bodies.is_empty());
+        assert_eq!(wood.len() + space.particles.particles.len(), 9);
+        assert!(wood.iter().any(|&(_, y)| y == 15), "{wood:?}");
+    }
+
+    #[test]
+    fn the_side_of_the_space_does_not_hold_anything() {
+        let mut space = Space::with_seed(24, 24, 0);
+        place(&mut space, CellType::ROCK, (9..24).map(|y| (21, y)));
+        place(&mut space, CellType::WOOD, (20..24).flat_map(|x| (6..9).map(move |y| (x, y))));
+        dig(&mut space, (21, 9));
+
+        // the block touches the right border, but only the rock below it reaches the floor
+        space.update_cell_behaviour();
+        assert_eq!(space.bodies.bodies.len(), 1);
+        assert!(find(&space, CellType::WOOD).is_empty());
+        assert_eq!(find(&space, CellType::ROCK).len(), 14);
+    }
+
+    #[test]
+    fn groups_that_reach_too_far_count_as_anchored() {
+        let mut space = Space::with_seed(96, 16, 0);
+        let metal = material("Metal");
+
+        // two floating beams, only the short one stays within the reach of the cell that was dug out below it
+        place(&mut space, metal, (10..30).map(|x| (x, 4)).chain((10..20 + MAX_BODY_REACH + 2).map(|x| (x, 10))));
+        place(&mut space, metal, [(20, 5), (20, 11)]);
+        dig(&mut space, (20, 5));
+        dig(&mut space, (20, 11));
+        space.update_cell_behaviour();
+
+        assert_eq!(space.bodies.bodies.len(), 1);
+        assert_eq!(space.bodies.bodies[0].cells.len(), 20);
+        assert_eq!(find(&space, metal).len(), MAX_BODY_REACH as usize + 12);
+    }
+}
@@ -0,0 +1,170 @@
+// imports:
+use std::collections::VecDeque;
+
+use crate::cells_layer::{DirtyRect, Space, StateOfAggregation};
+
+/// How far around a removed solid the load of the terrain is worked out again. Solids further away are assumed to hold, so every ```support_strength``` has to be smaller
+pub const STRUCTURE_REACH: i32 = 32;
+
+impl Space {
+
+    /// # Functionality:
+    /// Works out the load of the solid cells around ```removed``` and crumbles every cell that carries more than its ```support_strength```
+    /// # Behaviour:
+    /// The load of a solid cell is how far it is from the ground that holds it. It is passed on through ```StateOfAggregation::ImmovableSolid``` cells for free along the local gravity
+    /// and grows by one for every step sideways or against gravity, so overhangs and cave ceilings carry more the further they reach.
+    /// Only the area ```STRUCTURE_REACH``` cells around the removed cells is looked at. Solids at its edge count as ground, which is a deliberate approximation: the load is not walked out
+    /// to the real supports, so a span that reaches further than the area is judged as if it was held at its edge. A crumbled cell turns into its ```crumbles_into``` and counts as removed,
+    /// and every ```support_strength``` is smaller than ```STRUCTURE_REACH```, so such a span still crumbles back to the real load over the following ticks.
+    /// Of the border of the space only the floor that gravity points at counts as ground, the other sides are walls. Solids that are not connected to any ground are left to ```detach_floating_solids()```
+    pub fn update_structure(&mut self, removed: &[usize]) {
+        let Some(area) = removed.iter().map(|&i| {
+            let (x, y) = self.get_coordinates(i as isize);
+            DirtyRect { min_x: x, min_y: y, max_x: x, max_y: y }
+        }).reduce(DirtyRect::union) else { return };
+
+        let area = DirtyRect {
+            min_x: (area.min_x - STRUCTURE_REACH).max(0),
+            min_y: (area.min_y - STRUCTURE_REACH).max(0),
+            max_x: (area.max_x + STRUCTURE_REACH).min(self.width - 1),
+            max_y: (area.max_y + STRUCTURE_REACH).min(self.height - 1),
+        };
+        let width = area.max_x - area.min_x + 1;
+        let local = |(x, y): (i32, i32)| ((y - area.min_y) * width + x - area.min_x) as usize;
+        let solid = |i: isize| self.cells[i as usize].get_cell_properties().state == StateOfAggregation::ImmovableSolid;
+
+        // the ground has no load
+        let mut load = vec![u16::MAX; (width * (area.max_y - area.min_y + 1)) as usize];
+        let mut queue = VecDeque::new();
+        for y in area.min_y..=area.max_y {
+            for x in area.min_x..=area.max_x {
+                let i = self.get_index(x, y);
+                let reach_edge = (x == area.min_x && x > 0) || (y == area.min_y && y > 0) || (x == area.max_x && x < self.width - 1) || (y == area.max_y && y < self.height - 1);
+                let on_floor = self.get_neighbour(i, self.gravity_at(i as usize)).is_none();
+                if (reach_edge || on_floor) && solid(i) {
+                    load[local((x, y))] = 0;
+                    queue.push_back((x, y));
+                }
+            }
+        }
+
+        // breadth first search where free steps go to the front, so every cell is reached with its lowest load first
+        while let Some((x, y)) = queue.pop_front() {
+            let current = load[local((x, y))];
+            for (dx, dy) in [(0, -1), (0, 1), (-1, 0), (1, 0)] {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < area.min_x || ny < area.min_y || nx > area.max_x || ny > area.max_y { continue }
+                let j = self.get_index(nx, ny);
+                if !solid(j) { continue }
+
+                // the neighbour rests on this cell for free if this cell is below it
+                let free = self.gravity_at(j as usize).along((-dx as f32, -dy as f32)) > 0.0;
+                let next = if free { current } else { current + 1 };
+                if next < load[local((nx, ny))] {
+                    load[local((nx, ny))] = next;
+                    if free { queue.push_front((nx, ny)) } else { queue.push_back((nx, ny)) }
+                }
+            }
+        }
+
+        for y in area.min_y..=area.max_y {
+            for x in area.min_x..=area.max_x {
+                let carried = load[local((x, y))];
+                if carried == u16::MAX { continue }
+
+                let i = self.get_index(x, y) as usize;
+                let properties = self.cells[i].get_cell_properties();
+                if properties.support_strength.is_some_and(|strength| carried > strength) {
+                    self.convert_cell(i, properties.crumbles_into, self.cells[i].temp);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cells_layer::{CellType, Space};
+    use crate::test_support::{find, material, place};
+
+    /// fills the rows ```10..40``` of a 96 wide space with rock and digs out a cave of ```width``` in the middle of it
+    fn dig_cave(width: i32) -> Space {
+        let mut space = Space::with_seed(96, 40, 0);
+        place(&mut space, CellType::ROCK, (0..96).flat_map(|x| (10..40).map(move |y| (x, y))));
+        place(&mut space, CellType::AIR, (48 - width / 2..48 + width / 2).flat_map(|x| (20..30).map(move |y| (x, y))));
+        space
+    }
+
+    #[test]
+    fn narrow_caves_hold() {
+        let mut space = dig_cave(24);
+        for _ in 0..50 { space.update_cell_behaviour() }
+
+        assert_eq!(find(&space, CellType::ROCK).len(), 96 * 30 - 24 * 10);
+        assert!(find(&space, CellType::GRAVEL).is_empty());
+    }
+
+    #[test]
+    fn wide_caves_cave_in() {
+        let mut space = dig_cave(48);
+        for _ in 0..100 { space.update_cell_behaviour() }
+
+        // the middle of the ceiling came down as gravel, the parts close to the walls still hang
+        let gravel = find(&space, CellType::GRAVEL);
+        assert!(!gravel.is_empty());
+        assert!(gravel.iter().all(|&(x, y)| (24..72).contains(&x) && y >= 20), "{gravel:?}");
+        assert!(!find(&space, CellType::ROCK).contains(&(48, 10)));
+        assert!(find(&space, CellType::ROCK).contains(&(30, 19)));
+        assert!(find(&space, CellType::ROCK).contains(&(65, 19)));
+    }
+
+    #[test]
+    fn beams_break_once_their_prop_is_gone() {
+        let mut space = Space::with_seed(32, 24, 0);
+        place(&mut space, CellType::ROCK, (0..3).flat_map(|x| (0..24).map(move |y| (x, y))));
+        place(&mut space, CellType::WOOD, (3..24).map(|x| (x, 10)));
+        place(&mut space, CellType::ROCK, (11..24).map(|y| (23, y)));
+        for _ in 0..10 { space.update_cell_behaviour() }
+        assert_eq!(find(&space, CellType::WOOD).len(), 21);
+
+        place(&mut space, CellType::AIR, [(23, 11)]);
+        for _ in 0..100 { space.update_cell_behaviour() }
+
+        // a load of one per cell away from the wall, wood holds 10
+        let splinters = material("Splinters");
+        assert_eq!(find(&space, CellType::WOOD), (3..13).map(|x| (x, 10)).collect::<Vec<_>>());
+        assert_eq!(find(&space, splinters).len(), 11);
+    }
+
+    #[test]
+    fn the_side_of_the_space_does_not_carry_a_beam() {
+        let mut space = Space::with_seed(32, 24, 0);
+        place(&mut space, CellType::ROCK, (0..3).flat_map(|x| (0..24).map(move |y| (x, y))));
+        place(&mut space, CellType::WOOD, (3..32).map(|x| (x, 10)));
+        place(&mut space, CellType::ROCK, (11..24).map(|y| (23, y)));
+        for _ in 0..10 { space.update_cell_behaviour() }
+
+        // the beam reaches the right border, but only the wall on the left holds it
+        place(&mut space, CellType::AIR, [(23, 11)]);
+        for _ in 0..100 { space.update_cell_behaviour() }
+        assert_eq!(find(&space, CellType::WOOD), (3..13).map(|x| (x, 10)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn beams_longer_than_the_reach_crumble_back_over_the_following_ticks() {
+        let mut space = Space::with_seed(128, 24, 0);
+        place(&mut space, CellType::ROCK, (0..3).flat_map(|x| (0..24).map(move |y| (x, y))));
+        place(&mut space, CellType::WOOD, (3..100).map(|x| (x, 10)));
+        place(&mut space, CellType::ROCK, (11..24).map(|y| (99, y)));
+        for _ in 0..10 { space.update_cell_behaviour() }
+
+        // the first pass only sees the far end of the beam and treats the edge of its area as ground, so the wood breaks off in pieces
+        place(&mut space, CellType::AIR, [(99, 11)]);
+        space.update_cell_behaviour();
+        let wood = find(&space, CellType::WOOD);
+        assert!(wood.len() > 10 && wood.len() < 97, "{}", wood.len());
+
+        for _ in 0..300 { space.update_cell_behaviour() }
+        assert_eq!(find(&space, CellType::WOOD), (3..13).map(|x| (x, 10)).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,34 @@
+// helpers shared by the test modules of every layer
+
+// imports:
+use crate::cells_layer::{Cell, CellType, Space};
+use crate::material_registry::MaterialRegistry;
+
+/// returns the ```CellType``` of the material called ```name```
+pub fn material(name: &str) -> CellType { MaterialRegistry::get().find(name).unwrap() }
+
+/// places one ```cell_type``` cell at every ```(x, y)``` of ```positions```
+pub fn place(space: &mut Space, cell_type: CellType, positions: impl IntoIterator<Item = (i32, i32)>) {
+    for (x, y) in positions {
+        let cell = Cell::build_cell(cell_type, &mut space.rng);
+        space.set_cell(space.get_index(x, y) as usize, &cell);
+    }
+}
+
+/// returns the coordinates of every ```cell_type``` cell
+pub fn find(space: &Space, cell_type: CellType) -> Vec<(i32, i32)> {
+    (0..space.cells.len()).filter(|&i| space.cells[i].cell_type == cell_type).map(|i| space.get_coordinates(i as isize)).collect()
+}
+
+/// returns how many cells of ```cell_type``` there are
+pub fn count(space: &Space, cell_type: CellType) -> usize { space.cells.iter().filter(|cell| cell.cell_type == cell_type).count() }
+
+/// returns the cell at ```(x, y)```
+pub fn cell_at(space: &Space, (x, y): (i32, i32)) -> &Cell { &space.cells[space.get_index(x, y) as usize] }
+
+/// runs a full tick, movement, heat and alchemy
+pub fn tick(space: &mut Space) {
+    space.update_cell_behaviour();
+    space.update_cell_temperature();
+    space.update_cell_alchemy();
+}
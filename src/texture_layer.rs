@@ -0,0 +1,133 @@
+// imports:
+use serde::Deserialize;
+
+use crate::cells_layer::{Cell, CellType, Space};
+
+/// # Functionality:
+/// A pattern that a material is painted with, sampled by world position so that it lines up no matter where and when the cells are placed
+/// # Options:
+/// ```Flat``` has no pattern. ```Grain``` draws wavy stripes that run from top to bottom like the grain of wood and ```Strata``` draws wavy layers that run from left to right like rock.
+/// ```spacing``` is the distance between two stripes in cells and ```strength``` (0.0 to 1.0) how much lighter or darker the pattern makes the colour at most
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize)]
+pub enum Texture {
+    #[default]
+    Flat,
+    Grain { spacing: f32, strength: f32 },
+    Strata { spacing: f32, strength: f32 },
+}
+
+impl Texture {
+
+    /// # Functionality:
+    /// Returns the factor that the colour of a cell at the world coordinates ```(x, y)``` is multiplied with
+    /// # Behaviour:
+    /// The stripes are a sine wave across their direction, bent by a slow wave and a little noise along it, so they do not look ruled.
+    /// The waves are worked out in ```f64```, an ```f32``` can not tell neighbouring cells apart far away from the origin
+    pub fn sample(self, (x, y): (i64, i64)) -> f32 {
+        let stripes = |across: i64, along: i64, spacing: f32| {
+            let bend = 1.5 * (along as f64 * 0.13).sin() + 0.3 * noise((along, across / 4)) as f64;
+            ((across as f64 + bend) * std::f64::consts::TAU / spacing as f64).sin() as f32
+        };
+
+        match self {
+            Texture::Flat => 1.0,
+            Texture::Grain { spacing, strength } => 1.0 + strength * stripes(x, y, spacing),
+            Texture::Strata { spacing, strength } => 1.0 + strength * stripes(y, x, spacing),
+        }
+    }
+}
+
+/// # Functionality:
+/// Returns a pseudo random number between -1.0 and 1.0 that only depends on ```(x, y)```
+fn noise((x, y): (i64, i64)) -> f32 {
+    let mut hash = (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(0x1656_67B1_9E37_79F9);
+    hash ^= hash >> 32;
+    (hash & 0xFFFF) as f32 / 32767.5 - 1.0
+}
+
+/// # Functionality:
+/// Multiplies the red, green and blue of ```color``` by ```factor```, the alpha is kept
+pub fn shade(color: [u8; 4], factor: f32) -> [u8; 4] {
+    let [r, g, b, a] = color;
+    let channel = |value: u8| (value as f32 * factor).round().clamp(0.0, 255.0) as u8;
+    [channel(r), channel(g), channel(b), a]
+}
+
+impl Cell {
+
+    /// # Functionality:
+    /// Returns the cell with the ```texture``` of its material applied to its colour, as it looks at the world coordinates ```position```
+    pub fn textured(self, position: (i64, i64)) -> Cell {
+        Cell { color: shade(self.color, self.get_cell_properties().texture.sample(position)), ..self }
+    }
+}
+
+impl Space {
+
+    /// # Functionality:
+    /// Builds a fresh cell of ```cell_type``` that is meant to be painted at index i, see ```Cell::textured()```
+    /// # Panic behaviour:
+    /// Panics if ```i > self.lenght```
+    pub fn paint_cell(&mut self, i: usize, cell_type: CellType) -> Cell {
+        let (x, y) = self.get_coordinates(i as isize);
+        let position = (self.world_offset.0 + x as i64, self.world_offset.1 + y as i64);
+        Cell::build_cell(cell_type, &mut self.rng).textured(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn fresh_cells_vary_around_the_base_colour() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let properties = Cell::build_cell(CellType::SAND, &mut rng).get_cell_properties();
+        let colors: Vec<[u8; 4]> = (0..100).map(|_| Cell::build_cell(CellType::SAND, &mut rng).color).collect();
+
+        assert!(colors.iter().any(|color| *color != colors[0]));
+        for color in colors {
+            for channel in 0..3 {
+                let base = properties.base_color[channel] as f32;
+                assert!((color[channel] as f32 - base).abs() <= base * properties.color_variation + 1.0, "{color:?}");
+            }
+            assert_eq!(color[3], properties.base_color[3]);
+        }
+    }
+
+    #[test]
+    fn textures_are_sampled_by_world_position() {
+        let strata = Texture::Strata { spacing: 8.0, strength: 0.2 };
+        assert_eq!(strata.sample((1_000_000_000, 7)), strata.sample((1_000_000_000, 7)));
+        assert_eq!(Texture::Flat.sample((3, 4)), 1.0);
+
+        // strata change from layer to layer much more than along one, and neighbouring cells far away differ just as much as the ones close to the origin
+        let change = |origin: i64, (dx, dy): (i64, i64)| {
+            let cells = (0..64).flat_map(|x| (0..64).map(move |y| (origin + x, origin + y)));
+            cells.map(|(x, y)| (strata.sample((x + dx, y + dy)) - strata.sample((x, y))).abs()).sum::<f32>()
+        };
+        for origin in [0, 1_000_000_000, 1_000_000_000_000] {
+            assert!(change(origin, (0, 1)) > 3.0 * change(origin, (1, 0)), "{origin}");
+            for step in [(0, 1), (1, 0)] {
+                let (near, far) = (change(0, step), change(origin, step));
+                assert!((far - near).abs() < 0.15 * near, "{origin} {step:?}: {far} instead of {near}");
+            }
+        }
+    }
+
+    #[test]
+    fn painted_cells_line_up_across_spaces() {
+        let mut a = Space::with_seed(16, 16, 0);
+        let mut b = Space::with_seed(16, 16, 0);
+        b.world_offset = (8, 4);
+
+        // the same world position with colour variation taken out by the same seed
+        let i = a.get_index(10, 6) as usize;
+        let j = b.get_index(2, 2) as usize;
+        assert_eq!(a.paint_cell(i, CellType::WOOD).color, b.paint_cell(j, CellType::WOOD).color);
+    }
+}
@@ -0,0 +1,519 @@
+// imports:
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::cells_layer::{Cell, CellType, DirtyRect, Space, CHUNK_SIZE};
+use crate::material_registry::MaterialRegistry;
+use crate::particle_layer::Particle;
+use crate::rigid_body_layer::RigidBody;
+
+/// How many chunks are kept loaded in every direction around the anchor
+pub const LOAD_RADIUS: i64 = 2;
+
+/// How many chunks are simulated in every direction around the anchor. The loaded chunks beyond are frozen, they only give the simulated ones something real to touch
+pub const SIMULATION_RADIUS: i64 = 1;
+
+/// The edge length of the loaded area in chunks
+pub const LOADED_CHUNKS: i64 = LOAD_RADIUS * 2 + 1;
+
+/// Every cell with a world y coordinate of at least this is generated as rock
+pub const GROUND_LEVEL: i64 = 100;
+
+/// The size of a single cell in a chunk file, see ```encode_cell()```
+const CELL_BYTES: usize = 23;
+
+/// # Functionality:
+/// The generator of new chunks, returns the ```CellType``` of the cell at the world coordinates ```x, y```
+/// # Behaviour:
+/// Generates air above ```GROUND_LEVEL``` and rock below it
+pub fn flat_ground(_x: i64, y: i64) -> CellType {
+    if y >= GROUND_LEVEL { CellType::ROCK } else { CellType::AIR }
+}
+
+/// # Functionality:
+/// An endless world made of chunks that are identified by their 64 bit chunk coordinates
+/// # Structure:
+/// ```
+/// pub struct World {
+///     pub origin: (i64, i64),
+///     pub space: Space,
+///     pub save_directory: PathBuf,
+///     pub generator: fn(i64, i64) -> CellType,
+/// }
+/// ```
+/// Only the ```LOADED_CHUNKS * LOADED_CHUNKS``` chunks around the anchor are kept in ```space```, with ```origin``` being the chunk coordinates of its top left chunk.
+/// Of those only the chunks within ```SIMULATION_RADIUS``` are simulated, see ```Space::simulated```. The frozen ring around them is what cells run into at the edge of the simulated area,
+/// so it is neither a wall nor ground, and every search around a removed solid stays inside of the loaded cells, see ```Space::detach_floating_solids()``` and ```Space::update_structure()```.
+/// The simulation only ever sees the small local coordinates of ```space```, which is why it behaves the same no matter how far away from the world origin it runs.
+/// Chunks that fall out of range are stored in ```save_directory``` and loaded from there again, chunks that were never visited come from the ```generator```
+pub struct World {
+    pub origin: (i64, i64),
+    pub space: Space,
+    pub save_directory: PathBuf,
+    pub generator: fn(i64, i64) -> CellType,
+}
+
+impl World {
+    /// # Functionality:
+    /// Creates a world and loads the chunks around ```anchor```, which is given in world coordinates. New chunks are generated with ```flat_ground()```
+    /// # Behaviour:
+    /// The simulation draws its randomness from ```seed```, see ```Space::with_seed()```.
+    /// Fails if ```save_directory``` can not be created or one of the chunk files can not be read
+    pub fn new(anchor: (i64, i64), save_directory: impl AsRef<Path>, seed: u64) -> io::Result<World> {
+        World::with_generator(anchor, save_directory, seed, flat_ground)
+    }
+
+    /// # Functionality:
+    /// Creates a world like ```new()```, whose new chunks come from ```generator```
+    /// # Behaviour:
+    /// Fails if ```save_directory``` can not be created or one of the chunk files can not be read
+    pub fn with_generator(anchor: (i64, i64), save_directory: impl AsRef<Path>, seed: u64, generator: fn(i64, i64) -> CellType) -> io::Result<World> {
+        let size = (LOADED_CHUNKS * CHUNK_SIZE as i64) as i32;
+        let save_directory = save_directory.as_ref().to_path_buf();
+        fs::create_dir_all(&save_directory)?;
+
+        let origin = World::origin_around(anchor);
+        let mut space = Space::with_seed(size, size, seed);
+        space.world_offset = (origin.0 * CHUNK_SIZE as i64, origin.1 * CHUNK_SIZE as i64);
+        space.simulated = World::simulated_area();
+
+        // the chunks are loaded into their own space, which the world only takes over once it is complete
+        let mut world = World { origin, space: Space::new(0, 0), save_directory, generator };
+        for local in World::local_chunks() {
+            world.load_chunk(&mut space, origin, local)?;
+        }
+        world.space = space;
+        Ok(world)
+    }
+
+    /// # Functionality:
+    /// Returns the coordinates of the chunk that contains the world coordinates ```position```
+    pub fn chunk_of(position: (i64, i64)) -> (i64, i64) {
+        (position.0.div_euclid(CHUNK_SIZE as i64), position.1.div_euclid(CHUNK_SIZE as i64))
+    }
+
+    /// # Functionality:
+    /// Returns the ```origin``` of the loaded area if it is centered on the chunk of ```anchor```
+    fn origin_around(anchor: (i64, i64)) -> (i64, i64) {
+        let (x, y) = World::chunk_of(anchor);
+        (x - LOAD_RADIUS, y - LOAD_RADIUS)
+    }
+
+    /// # Functionality:
+    /// Returns the local chunk coordinates of all loaded chunks
+    fn local_chunks() -> impl Iterator<Item = (i32, i32)> {
+        (0..LOADED_CHUNKS as i32).flat_map(|y| (0..LOADED_CHUNKS as i32).map(move |x| (x, y)))
+    }
+
+    /// # Functionality:
+    /// Returns the cells of the chunks within ```SIMULATION_RADIUS``` of the anchor in local coordinates, which is the same for every ```origin```
+    pub fn simulated_area() -> DirtyRect {
+        let min = ((LOAD_RADIUS - SIMULATION_RADIUS) * CHUNK_SIZE as i64) as i32;
+        let max = ((LOAD_RADIUS + SIMULATION_RADIUS + 1) * CHUNK_SIZE as i64) as i32 - 1;
+        DirtyRect { min_x: min, min_y: min, max_x: max, max_y: max }
+    }
+
+    /// # Functionality:
+    /// Returns whether the chunk at the local chunk coordinates ```local``` is simulated
+    fn is_simulated_chunk(local: (i32, i32)) -> bool {
+        let range = (LOAD_RADIUS - SIMULATION_RADIUS) as i32..=(LOAD_RADIUS + SIMULATION_RADIUS) as i32;
+        range.contains(&local.0) && range.contains(&local.1)
+    }
+
+    /// # Functionality:
+    /// Converts world coordinates into coordinates of ```space```, returns ```None``` if they are not loaded
+    pub fn to_local(&self, position: (i64, i64)) -> Option<(i32, i32)> {
+        let x = position.0 - self.origin.0 * CHUNK_SIZE as i64;
+        let y = position.1 - self.origin.1 * CHUNK_SIZE as i64;
+        if x < 0 || y < 0 || x >= self.space.width as i64 || y >= self.space.height as i64 { return None }
+        Some((x as i32, y as i32))
+    }
+
+    /// # Functionality:
+    /// Converts coordinates of ```space``` into world coordinates
+    pub fn to_world(&self, local: (i32, i32)) -> (i64, i64) {
+        (self.origin.0 * CHUNK_SIZE as i64 + local.0 as i64, self.origin.1 * CHUNK_SIZE as i64 + local.1 as i64)
+    }
+
+    /// # Functionality:
+    /// Moves the loaded area so that it is centered on the chunk of ```anchor```, which is given in world coordinates
+    /// # Behaviour:
+    /// Does nothing as long as the anchor stays in the same chunk. Otherwise the chunks that stay are kept as they are including their dirty rects, particles, rigid bodies
+    /// and the solids whose removal was not handled yet, and the new ones are loaded or generated. Chunks that thaw out because they are simulated now are woken up.
+    /// Gravity fields move along with the world. Everything is built in a new space first and the chunks that fall out of range are only saved once that worked,
+    /// so if loading or saving fails the world is left unchanged
+    pub fn update_anchor(&mut self, anchor: (i64, i64)) -> io::Result<()> {
+        let origin = World::origin_around(anchor);
+        if origin == self.origin { return Ok(()) }
+
+        // how far everything that is kept moves in the new space
+        let shift = (((self.origin.0 - origin.0) * CHUNK_SIZE as i64) as i32, ((self.origin.1 - origin.1) * CHUNK_SIZE as i64) as i32);
+        let old = &self.space;
+
+        let mut space = Space::new(old.width, old.height);
+        space.generation = old.generation;
+        space.parallel = old.parallel;
+        space.sweep = old.sweep;
+        space.gravity = old.gravity.translated(shift);
+        space.rng = old.rng.clone();
+        space.world_offset = (origin.0 * CHUNK_SIZE as i64, origin.1 * CHUNK_SIZE as i64);
+        space.simulated = old.simulated;
+
+        // particles, bodies and removed solids are kept if they are still in range
+        for particle in &old.particles.particles {
+            let position = (particle.position.0 + shift.0 as f32, particle.position.1 + shift.1 as f32);
+            if space.particle_cell(position).is_some() {
+                space.particles.particles.push(Particle { position, ..*particle });
+            }
+        }
+        for body in &old.bodies.bodies {
+            let position = (body.position.0 + shift.0 as f32, body.position.1 + shift.1 as f32);
+            if space.particle_cell(position).is_some() {
+                space.bodies.bodies.push(RigidBody { position, ..body.clone() });
+            }
+        }
+        for &i in &old.bodies.removed_solids {
+            let (x, y) = old.get_coordinates(i as isize);
+            if let Ok(j) = space.get_index_checked(x + shift.0, y + shift.1) { space.bodies.removed_solids.push(j as usize) }
+        }
+
+        for local in World::local_chunks() {
+            let old_local = ((origin.0 - self.origin.0) as i32 + local.0, (origin.1 - self.origin.1) as i32 + local.1);
+            if !(0..LOADED_CHUNKS as i32).contains(&old_local.0) || !(0..LOADED_CHUNKS as i32).contains(&old_local.1) {
+                self.load_chunk(&mut space, origin, local)?;
+                continue;
+            }
+
+            move_chunk(old, old_local, &mut space, local);
+
+            // whatever a frozen chunk would have done is not known, so it is woken up once it is simulated
+            if World::is_simulated_chunk(local) && !World::is_simulated_chunk(old_local) {
+                let bounds = space.chunks[(local.0 + local.1 * space.chunks_x) as usize].bounds;
+                space.mark_dirty_rect(bounds);
+            }
+        }
+
+        let in_range = |chunk: (i64, i64)| {
+            (origin.0..origin.0 + LOADED_CHUNKS).contains(&chunk.0) && (origin.1..origin.1 + LOADED_CHUNKS).contains(&chunk.1)
+        };
+        for local in World::local_chunks() {
+            if !in_range((self.origin.0 + local.0 as i64, self.origin.1 + local.1 as i64)) { self.save_chunk(local)? }
+        }
+
+        self.space = space;
+        self.origin = origin;
+        Ok(())
+    }
+
+    /// # Functionality:
+    /// Saves every loaded chunk, for example before the game exits
+    pub fn save_all(&self) -> io::Result<()> {
+        for local in World::local_chunks() {
+            self.save_chunk(local)?;
+        }
+        Ok(())
+    }
+
+    /// # Functionality:
+    /// Returns the path of the file that the chunk with the world chunk coordinates ```chunk``` is stored in
+    pub fn chunk_path(&self, chunk: (i64, i64)) -> PathBuf {
+        self.save_directory.join(format!("chunk_{}_{}.bin", chunk.0, chunk.1))
+    }
+
+    /// # Functionality:
+    /// Writes the chunk at the local chunk coordinates ```local``` to its file
+    fn save_chunk(&self, local: (i32, i32)) -> io::Result<()> {
+        let mut bytes = Vec::with_capacity((CHUNK_SIZE * CHUNK_SIZE) as usize * CELL_BYTES);
+        for i in chunk_cells(&self.space, local) {
+            bytes.extend_from_slice(&encode_cell(&self.space.cells[i]));
+        }
+        fs::write(self.chunk_path((self.origin.0 + local.0 as i64, self.origin.1 + local.1 as i64)), bytes)
+    }
+
+    /// # Functionality:
+    /// Fills the chunk at the local chunk coordinates ```local``` of ```space```, whose top left chunk is ```origin```, from its file or from the ```generator``` if it has none
+    /// # Behaviour:
+    /// Loaded chunks are woken up if they are simulated, because whatever they did when they were saved is not stored. Generated chunks sleep until something touches them
+    fn load_chunk(&self, space: &mut Space, origin: (i64, i64), local: (i32, i32)) -> io::Result<()> {
+        let chunk = (origin.0 + local.0 as i64, origin.1 + local.1 as i64);
+        let cells = chunk_cells(space, local);
+
+        // a chunk that was never stored is new
+        let bytes = match fs::read(self.chunk_path(chunk)) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                for i in cells {
+                    let (x, y) = space.get_coordinates(i as isize);
+                    let position = (space.world_offset.0 + x as i64, space.world_offset.1 + y as i64);
+                    space.cells[i] = Cell::build_cell((self.generator)(position.0, position.1), &mut space.rng).textured(position);
+                }
+                return Ok(());
+            },
+            Err(err) => return Err(err),
+        };
+
+        if bytes.len() != cells.len() * CELL_BYTES {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a valid chunk", self.chunk_path(chunk).display())));
+        }
+        for (i, data) in cells.into_iter().zip(bytes.chunks_exact(CELL_BYTES)) {
+            let mut cell = decode_cell(data)?;
+
+            // the cell must be able to move in the current tick
+            cell.generation = space.generation.wrapping_sub(1);
+            space.cells[i] = cell;
+        }
+
+        let bounds = space.chunks[(local.0 + local.1 * space.chunks_x) as usize].bounds;
+        space.mark_dirty_rect(bounds);
+        Ok(())
+    }
+}
+
+/// # Functionality:
+/// Returns the indices of all cells of the chunk at the local chunk coordinates ```local```, row by row
+fn chunk_cells(space: &Space, local: (i32, i32)) -> Vec<usize> {
+    let (min_x, min_y) = (local.0 * CHUNK_SIZE, local.1 * CHUNK_SIZE);
+    (min_y..min_y + CHUNK_SIZE).flat_map(|y| (min_x..min_x + CHUNK_SIZE).map(move |x| (x, y))).map(|(x, y)| space.get_index(x, y) as usize).collect()
+}
+
+/// # Functionality:
+/// Copies the chunk at ```from_local``` in ```from``` to ```to_local``` in ```to```, together with the parts of its dirty rects that ```to``` simulates
+fn move_chunk(from: &Space, from_local: (i32, i32), to: &mut Space, to_local: (i32, i32)) {
+    for (i, j) in chunk_cells(from, from_local).into_iter().zip(chunk_cells(to, to_local)) {
+        to.cells[j] = from.cells[i];
+    }
+
+    let (dx, dy) = ((to_local.0 - from_local.0) * CHUNK_SIZE, (to_local.1 - from_local.1) * CHUNK_SIZE);
+    let chunk = from.chunks[(from_local.0 + from_local.1 * from.chunks_x) as usize];
+    let target = &mut to.chunks[(to_local.0 + to_local.1 * to.chunks_x) as usize];
+    let shift = |rect: DirtyRect| DirtyRect {
+        min_x: rect.min_x + dx,
+        min_y: rect.min_y + dy,
+        max_x: rect.max_x + dx,
+        max_y: rect.max_y + dy,
+    };
+    target.dirty = chunk.dirty.map(shift).and_then(|rect| rect.intersection(to.simulated));
+    target.next_dirty = chunk.next_dirty.map(shift).and_then(|rect| rect.intersection(to.simulated));
+}
+
+/// # Functionality:
+/// Turns a cell into the bytes that are stored in a chunk file
+/// # Structure:
+/// ```cell_type```, ```color```, ```temp```, ```lifetime```, ```strength```, ```velocity```, ```moisture```, ```charge``` and ```emits``` in little endian. The generation is not stored
+fn encode_cell(cell: &Cell) -> [u8; CELL_BYTES] {
+    let mut bytes = [0; CELL_BYTES];
+    bytes[0..2].copy_from_slice(&cell.cell_type.0.to_le_bytes());
+    bytes[2..6].copy_from_slice(&cell.color);
+    bytes[6..8].copy_from_slice(&cell.temp.to_le_bytes());
+    bytes[8..10].copy_from_slice(&cell.lifetime.to_le_bytes());
+    bytes[10] = cell.strength;
+    bytes[11..15].copy_from_slice(&cell.velocity.0.to_le_bytes());
+    bytes[15..19].copy_from_slice(&cell.velocity.1.to_le_bytes());
+    bytes[19] = cell.moisture;
+    bytes[20] = cell.charge;
+    bytes[21..23].copy_from_slice(&cell.emits.0.to_le_bytes());
+    bytes
+}
+
+/// # Functionality:
+/// Reads a cell written by ```encode_cell()```
+/// # Behaviour:
+/// Fails if the cell is of, or emits, a material that the ```MaterialRegistry``` does not know
+fn decode_cell(bytes: &[u8]) -> io::Result<Cell> {
+    let cell_type = CellType(u16::from_le_bytes([bytes[0], bytes[1]]));
+    let emits = CellType(u16::from_le_bytes([bytes[21], bytes[22]]));
+    for material in [cell_type, emits] {
+        if material.0 as usize >= MaterialRegistry::get().materials().len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown material id {}", material.0)));
+        }
+    }
+
+    Ok(Cell {
+        cell_type,
+        generation: 0,
+        color: [bytes[2], bytes[3], bytes[4], bytes[5]],
+        temp: u16::from_le_bytes([bytes[6], bytes[7]]),
+        lifetime: u16::from_le_bytes([bytes[8], bytes[9]]),
+        strength: bytes[10],
+        moisture: bytes[19],
+        charge: bytes[20],
+        emits,
+        velocity: (f32::from_le_bytes([bytes[11], bytes[12], bytes[13], bytes[14]]), f32::from_le_bytes([bytes[15], bytes[16], bytes[17], bytes[18]])),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{material, place};
+
+    /// returns an empty directory for the chunk files of one test
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("falling_sand_{name}_{}", std::process::id()));
+        fs::remove_dir_all(&directory).ok();
+        directory
+    }
+
+    fn air(_x: i64, _y: i64) -> CellType { CellType::AIR }
+
+    /// rock with a cave in it, repeating every 4096 cells so that it looks the same near the origin and far away from it
+    fn caves(x: i64, y: i64) -> CellType {
+        let (x, y) = (x.rem_euclid(4096), y.rem_euclid(4096));
+        if (8..40).contains(&x) && (48..60).contains(&y) { return CellType::AIR }
+        if (40..2048).contains(&y) { CellType::ROCK } else { CellType::AIR }
+    }
+
+    /// places ```cell_type``` at the world coordinates of ```positions```
+    fn place_at(world: &mut World, cell_type: CellType, positions: impl IntoIterator<Item = (i64, i64)>) {
+        let local: Vec<(i32, i32)> = positions.into_iter().map(|position| world.to_local(position).unwrap()).collect();
+        place(&mut world.space, cell_type, local);
+    }
+
+    /// the cells of the world without their generation, which is not stored
+    fn snapshot(world: &World) -> Vec<Cell> {
+        world.space.cells.iter().map(|cell| Cell { generation: 0, ..*cell }).collect()
+    }
+
+    fn tick(world: &mut World) {
+        world.space.update_cell_behaviour();
+        world.space.update_cell_temperature();
+        world.space.update_cell_alchemy();
+    }
+
+    #[test]
+    fn cells_keep_every_field_in_a_chunk_file() {
+        let cell = Cell {
+            cell_type: material("Source"),
+            color: [1, 2, 3, 4],
+            generation: 0,
+            temp: 1234,
+            lifetime: 567,
+            strength: 89,
+            moisture: 201,
+            charge: 7,
+            emits: CellType::LAVA,
+            velocity: (1.5, -2.25),
+        };
+        assert_eq!(decode_cell(&encode_cell(&cell)).unwrap(), cell);
+
+        // ids that the registry does not know are rejected, both as the material and as what it emits
+        let mut bytes = encode_cell(&cell);
+        bytes[21..23].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert_eq!(decode_cell(&bytes).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        bytes[0..2].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert_eq!(decode_cell(&bytes).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn chunks_come_back_unchanged_after_the_anchor_leaves_and_returns() {
+        let save_directory = directory("return");
+        let mut world = World::new((32, 32), &save_directory, 0).unwrap();
+        place_at(&mut world, CellType::SAND, (0..20).map(|x| (x, 99)));
+        place_at(&mut world, CellType::WATER, (0..20).map(|x| (x, -100)));
+        let local = world.to_local((5, 5)).unwrap();
+        let i = world.space.get_index(local.0, local.1) as usize;
+        world.space.cells[i] = Cell { temp: 900, lifetime: 12, strength: 3, moisture: 40, charge: 2, emits: CellType::SAND, velocity: (0.5, 2.0), ..world.space.cells[i] };
+        let before = snapshot(&world);
+
+        // far enough that every chunk is saved, and back again so that every chunk is loaded
+        world.update_anchor((32 + 10 * CHUNK_SIZE as i64, 32)).unwrap();
+        assert!(world.to_local((5, 5)).is_none());
+        world.update_anchor((32, 32)).unwrap();
+
+        assert_eq!(world.origin, World::origin_around((32, 32)));
+        assert!(snapshot(&world) == before);
+        fs::remove_dir_all(save_directory).ok();
+    }
+
+    #[test]
+    fn the_world_behaves_the_same_far_away_from_the_origin() {
+        let run = |anchor: (i64, i64), name: &str| {
+            let save_directory = directory(name);
+            let mut world = World::with_generator(anchor, &save_directory, 0, caves).unwrap();
+            let at = |(x, y): (i64, i64)| (anchor.0 + x, anchor.1 + y);
+
+            // a hole in the cave ceiling with sand and water pouring through it
+            place_at(&mut world, CellType::AIR, (20..26).flat_map(|x| (40..48).map(move |y| at((x, y)))));
+            place_at(&mut world, CellType::SAND, (10..30).flat_map(|x| (20..30).map(move |y| at((x, y)))));
+            place_at(&mut world, CellType::WATER, (30..50).flat_map(|x| (25..35).map(move |y| at((x, y)))));
+
+            // the world moves along with the anchor in between
+            for step in 0..150 {
+                if step == 50 { world.update_anchor(at((CHUNK_SIZE as i64, 0))).unwrap() }
+                if step == 100 { world.update_anchor(anchor).unwrap() }
+                tick(&mut world);
+            }
+            fs::remove_dir_all(save_directory).ok();
+
+            let cells: Vec<(CellType, u16)> = world.space.cells.iter().map(|cell| (cell.cell_type, cell.temp)).collect();
+            (cells, world.space.particles.particles.len(), world.space.bodies.bodies.len())
+        };
+
+        let near = run((0, 0), "near");
+        let far = run((1_000_000_000_000, -1_000_000_000_000), "far");
+        assert!(near.0.iter().any(|(cell_type, _)| *cell_type == CellType::SAND));
+        assert!(near == far);
+    }
+
+    #[test]
+    fn the_edge_of_the_simulated_area_is_neither_a_wall_nor_ground() {
+        let save_directory = directory("edge");
+        let mut world = World::with_generator((32, 32), &save_directory, 0, air).unwrap();
+        let bottom = world.to_world((0, World::simulated_area().max_y)).1;
+
+        // sand falls past the last simulated row into the frozen ring and stays there
+        place_at(&mut world, CellType::SAND, [(32, bottom)]);
+        for _ in 0..5 { tick(&mut world) }
+        let sand: Vec<i64> = (bottom - 10..bottom + 20).filter(|&y| {
+            let (x, y) = world.to_local((32, y)).unwrap();
+            world.space.cells[world.space.get_index(x, y) as usize].cell_type == CellType::SAND
+        }).collect();
+        assert!(sand.len() == 1 && sand[0] > bottom, "{sand:?}");
+        fs::remove_dir_all(&save_directory).ok();
+
+        // a block resting on the last simulated row falls just like one in the middle of the simulated area
+        for anchor in [(32, 32), (32, 32 + CHUNK_SIZE as i64)] {
+            let mut world = World::with_generator(anchor, &save_directory, 0, air).unwrap();
+            place_at(&mut world, CellType::ROCK, (10..17).flat_map(|x| (bottom - 5..=bottom).map(move |y| (x, y))));
+            place_at(&mut world, CellType::AIR, [(16, bottom - 2)]);
+            tick(&mut world);
+            assert_eq!(world.space.bodies.bodies.len(), 1, "{anchor:?}");
+            fs::remove_dir_all(&save_directory).ok();
+        }
+    }
+
+    #[test]
+    fn solids_removed_before_a_move_are_still_handled() {
+        let save_directory = directory("removed");
+        let mut world = World::with_generator((32, 32), &save_directory, 0, air).unwrap();
+        place_at(&mut world, CellType::ROCK, (10..17).flat_map(|x| (40..46).map(move |y| (x, y))));
+        place_at(&mut world, CellType::AIR, [(16, 43)]);
+
+        world.update_anchor((32 + CHUNK_SIZE as i64, 32)).unwrap();
+        tick(&mut world);
+
+        assert_eq!(world.space.bodies.bodies.len(), 1);
+        assert!(world.space.bodies.removed_solids.is_empty());
+        fs::remove_dir_all(save_directory).ok();
+    }
+
+    #[test]
+    fn a_chunk_that_can_not_be_loaded_leaves_the_world_unchanged() {
+        let save_directory = directory("broken");
+        let mut world = World::new((32, 32), &save_directory, 0).unwrap();
+        place_at(&mut world, CellType::SAND, (0..20).map(|x| (x, 50)));
+        let (origin, before) = (world.origin, snapshot(&world));
+
+        // the chunk that moving right would load next is broken
+        let entering = (origin.0 + LOADED_CHUNKS, origin.1);
+        fs::write(world.chunk_path(entering), [0; 7]).unwrap();
+        let err = world.update_anchor((32 + CHUNK_SIZE as i64, 32)).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(world.origin, origin);
+        assert!(snapshot(&world) == before);
+        assert!(!world.chunk_path(origin).exists());
+        fs::remove_dir_all(save_directory).ok();
+    }
+}